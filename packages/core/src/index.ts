@@ -3,6 +3,7 @@ export {
     isAccountId,
 
     BlockchainId,
+    HDPathTemplate,
 
     BlockchainType,
     AccountType,
@@ -12,19 +13,38 @@ export {
     UnsignedTx,
 
     AddressBookItem,
+    AddressBookItemUpdate,
+    AddressBookEns,
+    AddressBookAddResult,
+    AddressBookFormat,
+    AddressBookImportMode,
+    AddressBookImportRow,
+    AddressBookImportResult,
+    AddressBookFilter,
+    AddressBookPage,
     Wallet,
+    WalletUpdate,
     WalletAccount,
+    WalletAccountUpdate,
     EthereumAccount,
     isEthereumAccount,
 
     SeedPKRef,
     SeedAccount,
+    WatchOnlyAccount,
     SeedDefinition,
+    SeedReference,
     ImportMnemonic,
     MnemonicSeed,
     RawSeed,
     LedgerSeed,
+    LedgerDevice,
+    LedgerApp,
+    LedgerAddressConfirmation,
     SeedDescription,
+    SeedRemoveResult,
+    SeedExport,
+    HDPathAddress,
     isLedger,
     isMnemonic,
     isRawSeed,
@@ -1,7 +1,7 @@
 export type BlockchainType = "ethereum";
 export type SeedType = "raw" | "ledger" | "mnemonic";
-export type AccountType = "pk" | "seed-hd";
-export type ImportPkType = "ethereum-json" | "raw-pk-hex" | "hd-path" | "generate-random";
+export type AccountType = "raw-pk" | "seed-hd" | "ledger" | "watch-only";
+export type ImportPkType = "ethereum-json" | "raw-pk-hex" | "hd-path" | "generate-random" | "watch-only";
 
 export enum BlockchainId {
     ETHEREUM = 100,
@@ -9,6 +9,16 @@ export enum BlockchainId {
     KOVAN_TESTNET = 10002
 }
 
+/**
+ * HD Path templates for address discovery, where `*` is replaced with the scanned index
+ */
+export enum HDPathTemplate {
+    BIP44_ETHEREUM = "m/44'/60'/0'/0/*",
+    BIP44_ETHEREUM_CLASSIC = "m/44'/61'/0'/0/*",
+    LEDGER_LIVE = "m/44'/60'/*'/0/0",
+    LEGACY_MEW = "m/44'/60'/0'/*",
+}
+
 /**
  * UUID string, identifier of a Wallet/Seed/etc
  */
@@ -43,10 +53,94 @@ export type ImportMnemonic = {
 }
 
 export type AddressBookItem = {
+    // assigned by the vault, ignored when a new item is added
+    id?: Uuid,
     address: string,
     description?: string,
     name?: string,
-    blockchain: number
+    blockchain: number,
+    // groups of the item, like "exchange" or "payroll"
+    tags?: string[],
+    // free-form details of the item
+    metadata?: { [key: string]: string },
+    // ENS name resolved to the address. The vault doesn't resolve names, it's done by the caller
+    ens?: AddressBookEns
+}
+
+export type AddressBookEns = {
+    name: string,
+    // when the name was resolved to the address, as milliseconds since epoch. Current time is used if not set
+    resolvedAt?: number
+}
+
+/**
+ * Result of adding an item to the Address Book. An item with an address which is already on the same blockchain is
+ * merged into the existing item if it adds a missing name, description, tag or metadata key, or rejected otherwise
+ */
+export type AddressBookAddResult = {
+    result: "created" | "merged" | "rejected",
+    // id of the created or merged item, or of the existing item if it's rejected as a duplicate
+    id?: Uuid,
+    reason?: "invalid-address" | "invalid-checksum" | "invalid-ens-name" | "duplicate"
+}
+
+/**
+ * Format of exported Address Book. JSON is an array of objects with fields: blockchain, address, name, description,
+ * tags, metadata and ens (as {name, resolvedAt}). CSV has a header with the same columns, except tags are separated
+ * by ";", metadata is a JSON object, and ENS is in columns ens and ensResolvedAt. Columns after description are
+ * optional on import
+ */
+export type AddressBookFormat = "csv" | "json";
+
+/**
+ * How imported items are added. With "merge" they're added the same way as with addToAddressBook, "replace" removes
+ * all existing items before the import
+ */
+export type AddressBookImportMode = "merge" | "replace";
+
+export type AddressBookImportRow = {
+    // number of the imported item, starting from 1 and not counting the CSV header
+    row: number,
+    result: "created" | "merged" | "rejected",
+    id?: Uuid,
+    reason?: "invalid-address" | "invalid-checksum" | "invalid-ens-name" | "duplicate" | "invalid-row" | "invalid-blockchain"
+}
+
+export type AddressBookImportResult = {
+    rows: AddressBookImportRow[],
+    created: number,
+    merged: number,
+    rejected: number,
+    // items removed before the import in the "replace" mode
+    removed: number
+}
+
+export type AddressBookFilter = {
+    blockchain?: number,
+    // case-insensitive text to find in the name, description or address
+    query?: string,
+    // case-insensitive tag which the items must have
+    tag?: string,
+    offset?: number,
+    limit?: number
+}
+
+export type AddressBookPage = {
+    items: AddressBookItem[],
+    // number of all items matching the filter, regardless of offset and limit
+    total: number
+}
+
+/**
+ * Changes of an Address Book item. A field which is not set keeps the current value, null or an empty string clears it
+ */
+export type AddressBookItemUpdate = Update & {
+    address?: string,
+    // replaces all tags of the item
+    tags?: string[],
+    // replaces the whole metadata of the item
+    metadata?: { [key: string]: string },
+    ens?: AddressBookEns | null
 }
 
 export type ImportPrivateKey = {
@@ -56,11 +150,17 @@ export type ImportPrivateKey = {
     password: string
 }
 
+/**
+ * Key stored in the vault ("raw-pk"), or no key for a "watch-only" account
+ */
 export type PKRef = {
     type: AccountType,
-    keyId: Uuid
+    keyId?: Uuid | null
 }
 
+/**
+ * Key derived from a seed stored in the vault ("seed-hd"), or from a Hardware Wallet ("ledger")
+ */
 export type SeedPKRef = {
     type: AccountType,
     seedId: Uuid,
@@ -71,24 +171,58 @@ export type EthereumAccount = {
     id: AccountId,
     blockchain: number,
     address: string,
+    watchOnly?: boolean,
+    label?: string | undefined,
+    description?: string | undefined,
+    hidden?: boolean,
     key: PKRef | SeedPKRef | undefined
 }
 
 export type BitcoinAccount = {
     id: AccountId,
     blockchain: number,
+    label?: string | undefined,
+    description?: string | undefined,
+    hidden?: boolean,
     key: PKRef | SeedPKRef
 }
 
+/**
+ * Changes of a Wallet Account. A field which is not set keeps the current value, null or an empty string clears it
+ */
+export type WalletAccountUpdate = {
+    label?: string | null,
+    description?: string | null,
+    // hidden account is not listed unless the vault is opened with showHidden
+    hidden?: boolean
+}
+
 export type WalletAccount = EthereumAccount | BitcoinAccount;
 
 export type Wallet = {
     id: Uuid,
     name?: string | undefined,
     description?: string | undefined,
+    // UI hints, color code like "#ff8800" and icon code
+    color?: string | undefined,
+    icon?: string | undefined,
+    // timestamps in milliseconds, not set for wallets created by older versions
+    createdAt?: number | undefined,
+    updatedAt?: number | undefined,
+    // position in the list of wallets, wallets without it are listed after the ordered ones
+    order?: number | undefined,
     accounts: WalletAccount[],
 }
 
+/**
+ * Changes of a Wallet. A field which is not set keeps the current value, null or an empty string clears it
+ */
+export type WalletUpdate = Update & {
+    color?: string | null,
+    icon?: string | null,
+    order?: number | null
+}
+
 export function isEthereumAccount(acc: WalletAccount): acc is EthereumAccount {
     return acc.blockchain === 100 || acc.blockchain === 101
 }
@@ -129,20 +263,59 @@ export function extractAccountInternalId(id: AccountId): number {
 export type AddAccount = {
     blockchain: number,
     type: ImportPkType,
-    key?: string | SeedAccount,
+    key?: string | SeedAccount | WatchOnlyAccount,
     password?: string
 }
 
 export type SeedAccount = {
     seedId: Uuid,
     hdPath: string,
-    password?: string
+    password?: string,
+    // expected address, verified against the seed before the account is created
+    address?: string
+}
+
+/**
+ * Account without a private key. Either a single address, or a range of addresses at xpub/0/{start..start+count}.
+ * The range is imported once, as a separate account per address; the xpub is not kept and no more addresses are
+ * derived from it later
+ */
+export type WatchOnlyAccount = {
+    address?: string,
+    xpub?: string,
+    start?: number,
+    count?: number
+}
+
+export type HDPathAddress = {
+    address: string,
+    hdPath: string
 }
 
 export type SeedDescription = {
     id?: Uuid,
     type: SeedType,
-    available: boolean
+    available: boolean,
+    label?: string,
+    createdAt?: number,
+    // ids of the accounts which use the seed
+    accounts?: AccountId[]
+}
+
+export type SeedRemoveResult = {
+    removed: boolean,
+    // accounts using the seed, which are not usable after removal
+    accounts: AccountId[]
+}
+
+/**
+ * Backup of a seed, as a mnemonic phrase, or as hex of the raw seed bytes for seeds imported without a phrase
+ */
+export type SeedExport = {
+    type: "mnemonic" | "raw",
+    value: string,
+    // for a mnemonic, true if the seed was created with a passphrase, which is required to restore the same seed
+    passphrase?: boolean | null
 }
 
 export type SeedDefinition = {
@@ -152,18 +325,52 @@ export type SeedDefinition = {
     password?: string
 }
 
+/**
+ * Reference to a seed already stored in the vault, with password to decrypt it
+ */
+export type SeedReference = {
+    type: "seed-ref",
+    value: Uuid,
+    password?: string
+}
+
 export type MnemonicSeed = {
     value: string,
     password?: string
 }
 
 export type LedgerSeed = {
+    // id of the device to use, as returned by listLedgerDevices(). The first connected device is used if not set
+    device?: string
+}
 
+export type LedgerDevice = {
+    id: string,
+    vendor: "ledger" | "trezor",
+    // version of the Ethereum App if it's opened on a Ledger, or version of the Trezor firmware
+    appVersion?: string
+}
+
+export type LedgerApp = {
+    connected: boolean,
+    // locked with PIN
+    locked: boolean,
+    // name of the opened app, or "BOLOS" if it's on the dashboard of a Ledger. Always "Trezor" for a Trezor
+    app?: string,
+    version?: string,
+    // true if the Ethereum App is opened and the device is ready to use
+    ethereum: boolean
+}
+
+export type LedgerAddressConfirmation = {
+    // address shown on the device, if the user confirmed it
+    address?: string,
+    confirmed: boolean
 }
 
 export type RawSeed = string;
 
-export function isReference(seed: Uuid | SeedDefinition): seed is Uuid {
+export function isReference(seed: Uuid | SeedDefinition | SeedReference): seed is Uuid {
     return typeof seed === "string";
 }
 
@@ -190,10 +397,34 @@ export interface IEmeraldVault {
 
     setWalletLabel(walletId: Uuid, label: string): boolean;
 
+    updateWallet(walletId: Uuid, update: WalletUpdate): boolean;
+
     removeWallet(walletId: Uuid): void;
 
+    /**
+     * Moves accounts of all the wallets into a new wallet and removes the original wallets. An account with the same
+     * address on the same blockchain is added only once. The new wallet keeps the label, if it's not provided, and the
+     * details of the first wallet which has them
+     * @return id of the new wallet
+     */
+    mergeWallets(walletIds: Uuid[], label?: string): Uuid;
+
+    /**
+     * Moves the accounts, which must belong to the same wallet, into a new wallet
+     * @return id of the new wallet
+     */
+    splitWallet(accountIds: AccountId[], label?: string): Uuid;
+
     addAccount(walletId: Uuid, account: AddAccount): AccountId;
 
+    updateAccount(accountId: AccountId, update: WalletAccountUpdate): boolean;
+
+    /**
+     * Moves an account with its key to another wallet
+     * @return new id of the account
+     */
+    moveAccount(accountId: AccountId, targetWalletId: Uuid): AccountId;
+
     removeAccount(accountId: AccountId): boolean;
 
     signTx(accountId: AccountId, tx: UnsignedTx, password?: string): string;
@@ -206,17 +437,63 @@ export interface IEmeraldVault {
 
     listAddressBook(blockchain: number): AddressBookItem[];
 
-    addToAddressBook(item: AddressBookItem): boolean;
+    queryAddressBook(filter: AddressBookFilter): AddressBookPage;
+
+    /**
+     * Finds items by ENS name, on all blockchains
+     */
+    findAddressBookByName(name: string): AddressBookItem[];
+
+    addToAddressBook(item: AddressBookItem): AddressBookAddResult;
+
+    /**
+     * Removes items with the address on the blockchain, returns how many were removed
+     */
+    removeFromAddressBook(blockchain: number, address: string): number;
 
-    removeFromAddressBook(blockchain: number, address: string): boolean;
+    /**
+     * Removes the item with the id, returns how many were removed, i.e. 0 if it doesn't exist
+     */
+    removeAddressBookItem(id: Uuid): number;
+
+    exportAddressBook(format: AddressBookFormat): string;
+
+    importAddressBook(data: string, format: AddressBookFormat, mode?: AddressBookImportMode): AddressBookImportResult;
+
+    /**
+     * Updates the item in place. Returns false if there is no item with the id. Throws with a reason
+     * (invalid-address, invalid-checksum or duplicate) if the new address can't be used, and keeps the item as is
+     */
+    updateAddressBookItem(id: Uuid, update: AddressBookItemUpdate): boolean;
 
     listSeeds(): SeedDescription[];
 
+    setSeedLabel(seedId: Uuid, label: string): boolean;
+
+    removeSeed(seedId: Uuid, password: string, force?: boolean): SeedRemoveResult;
+
+    exportSeed(seedId: Uuid, password: string): SeedExport;
+
+    getXpub(seedId: Uuid, password: string, hdPath: string): string;
+
+    scanSeedAddresses(seedId: Uuid, password: string, basePath: string, startIndex: number, count: number): HDPathAddress[];
+
     getConnectedHWSeed(create: boolean): SeedDescription | undefined;
 
+    listLedgerDevices(): LedgerDevice[];
+
+    getLedgerApp(device?: string): LedgerApp;
+
+    confirmLedgerAddress(hdPath: string, device?: string): LedgerAddressConfirmation;
+
     importSeed(seed: SeedDefinition): Uuid;
 
     isSeedAvailable(seed: Uuid | SeedDefinition): boolean;
 
-    listSeedAddresses(seed: Uuid | SeedDefinition, blockchain: BlockchainType, hdpath: string[]): { [key: string]: string };
+    /**
+     * Addresses of the seed at the HD Paths. A seed stored in the vault is referenced by its id, which is enough for
+     * a Ledger seed, but a raw seed requires its password, i.e. a SeedReference. Fails with VAULT_ERROR
+     * ("Password is required") if it's not provided
+     */
+    listSeedAddresses(seed: Uuid | SeedDefinition | SeedReference, blockchain: BlockchainType, hdpath: string[]): { [key: string]: string };
 }
\ No newline at end of file
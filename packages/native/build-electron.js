@@ -5,7 +5,13 @@ var opts = {
     electron: "7.0.0"
 };
 
-build(["neon", "build", "--release"], opts, function(err) {
+var command = ["neon", "build", "--release"];
+if (process.argv.includes("--test")) {
+    // tests use the Hardware Wallet emulators, which aren't a part of the release build
+    command.push("--", "--features", "hw-emulator");
+}
+
+build(command, opts, function(err) {
     if (err) {
         console.error('Electron build failed.');
         console.error(err);
@@ -4,7 +4,7 @@ use std::str::FromStr;
 
 use neon::handle::Handle;
 use neon::object::Object;
-use neon::prelude::{FunctionContext, JsObject, JsString};
+use neon::prelude::{Context, FunctionContext, JsBoolean, JsObject, JsString, NeonResult};
 use neon::types::{JsNull, JsUndefined,};
 
 use emerald_vault::{
@@ -21,11 +21,13 @@ use emerald_vault::{
         wallet::{Wallet, WalletAccount},
     }
 };
+use hardware::HardwareType;
 
 pub struct VaultConfig {
     pub chain: Option<EthereumChainId>,
     pub dir: String,
-    pub show_hidden: bool
+    pub show_hidden: bool,
+    pub hardware: HardwareType
 }
 
 pub struct MigrationConfig {
@@ -47,6 +49,19 @@ pub fn obj_get_str(cx: &mut FunctionContext, obj: &Handle<JsObject>, name: &str)
     }
 }
 
+pub fn obj_get_bool(cx: &mut FunctionContext, obj: &Handle<JsObject>, name: &str) -> Option<bool> {
+    match obj.get(cx, name) {
+        Ok(val) => {
+            if val.is_a::<JsBoolean>() {
+                Some(val.downcast::<JsBoolean>().expect("Not a boolean").value())
+            } else {
+                None
+            }
+        },
+        Err(_) => None
+    }
+}
+
 pub fn args_get_str(cx: &mut FunctionContext, pos: i32) -> Option<String> {
     match cx.argument_opt(pos) {
         None => None,
@@ -61,9 +76,23 @@ pub fn args_get_str(cx: &mut FunctionContext, pos: i32) -> Option<String> {
     }
 }
 
+pub fn args_get_bool(cx: &mut FunctionContext, pos: i32) -> Option<bool> {
+    match cx.argument_opt(pos) {
+        None => None,
+        Some(v) => if v.is_a::<JsBoolean>() {
+            match v.downcast::<JsBoolean>() {
+                Ok(v) => Some(v.value()),
+                Err(_) => None
+            }
+        } else {
+            None
+        }
+    }
+}
+
 impl VaultConfig {
 
-    pub fn get_config(cx: &mut FunctionContext) -> VaultConfig {
+    pub fn get_config(cx: &mut FunctionContext) -> NeonResult<VaultConfig> {
         let config = cx.argument::<JsObject>(0).expect("Vault Config is not provided");
         let dir = match obj_get_str(cx, &config, "dir") {
             Some(val) => val,
@@ -75,11 +104,22 @@ impl VaultConfig {
             None => None
         };
 
-        return VaultConfig {
+        let show_hidden = obj_get_bool(cx, &config, "showHidden").unwrap_or(false);
+
+        let hardware = match obj_get_str(cx, &config, "hardware") {
+            Some(hardware) => match HardwareType::from_str(hardware.as_str()) {
+                Ok(hardware) => hardware,
+                Err(_) => return cx.throw_error(format!("Invalid hardware type: {}", hardware))
+            },
+            None => HardwareType::Hid
+        };
+
+        Ok(VaultConfig {
             chain,
             dir: dir.to_string(),
-            show_hidden: false
-        }
+            show_hidden,
+            hardware
+        })
     }
 
     pub fn get_storage(&self) -> VaultStorage {
@@ -17,7 +17,8 @@ use emerald_vault::{
     storage::error::VaultError,
     storage::keyfile::AccountInfo
 };
-use json::{StatusResult};
+use json::{StatusResult, status_code};
+use wallets::is_watch_only;
 
 pub struct AccountData {
     pub address: String,
@@ -136,25 +137,31 @@ impl WrappedVault {
         id
     }
 
-    fn export_pk(&self, wallet_id: Uuid, account_id: usize, password: String) -> PrivateKey {
+    fn export_pk(&self, wallet_id: Uuid, account_id: usize, password: String) -> StatusResult<PrivateKey> {
         let storage = &self.cfg.get_storage();
 
         let wallet = storage.wallets().get(&wallet_id).expect("Wallet doesn't exit");
         let account = wallet.get_account(account_id).expect("Account doesn't exist");
-        account.export_pk(password, storage).expect("PrivateKey unavailable")
+        if is_watch_only(&account) {
+            return StatusResult::Error(status_code::WATCH_ONLY, "Watch-only account has no private key".to_string())
+        }
+        StatusResult::from(account.export_pk(password, storage))
     }
 
-    fn export_web3(&self, wallet_id: Uuid, account_id: usize, password: Option<String>) -> EthereumJsonV3File {
+    fn export_web3(&self, wallet_id: Uuid, account_id: usize, password: Option<String>) -> StatusResult<EthereumJsonV3File> {
         let storage = &self.cfg.get_storage();
 
         let wallet = storage.wallets().get(&wallet_id).expect("Wallet doesn't exit");
         let account = wallet.get_account(account_id).expect("Account doesn't exist");
-        account.export_web3(password, storage).expect("PrivateKey unavailable")
+        if is_watch_only(&account) {
+            return StatusResult::Error(status_code::WATCH_ONLY, "Watch-only account has no private key".to_string())
+        }
+        StatusResult::from(account.export_web3(password, storage))
     }
 }
 
 pub fn import_ethereum(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let raw = cx.argument::<JsString>(1).expect("Input JSON is not provided").value();
@@ -173,7 +180,7 @@ pub fn import_ethereum(mut cx: FunctionContext) -> JsResult<JsObject> {
 
 
 pub fn export(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let wallet_id = cx.argument::<JsString>(1).expect("wallet_id not provided").value();
@@ -182,15 +189,15 @@ pub fn export(mut cx: FunctionContext) -> JsResult<JsObject> {
 
     let password = args_get_str(&mut cx, 3);
 
-    let pk = vault.export_web3(wallet_id, account_id, password);
-    let result = serde_json::to_string_pretty(&pk).expect("Failed to convert to JSON");
-    let status = StatusResult::Ok(result).as_json();
+    let result = vault.export_web3(wallet_id, account_id, password)
+        .map(|pk| serde_json::to_string_pretty(&pk).expect("Failed to convert to JSON"));
+    let status = result.as_json();
     let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
     Ok(js_value.downcast().unwrap())
 }
 
 pub fn export_pk(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let wallet_id = cx.argument::<JsString>(1).expect("wallet_id not provided").value();
@@ -198,15 +205,15 @@ pub fn export_pk(mut cx: FunctionContext) -> JsResult<JsObject> {
     let account_id = cx.argument::<JsNumber>(2).expect("account_id not provided").value() as usize;
     let password = cx.argument::<JsString>(3).expect("Password is not provided").value();
 
-    let pk = vault.export_pk(wallet_id, account_id, password);
-    let result = format!("0x{}", pk.to_hex());
-    let status = StatusResult::Ok(result).as_json();
+    let result = vault.export_pk(wallet_id, account_id, password)
+        .map(|pk| format!("0x{}", pk.to_hex()));
+    let status = result.as_json();
     let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
     Ok(js_value.downcast().unwrap())
 }
 
 pub fn import_mnemonic(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let raw = cx.argument::<JsString>(1).expect("Input JSON is not provided").value();
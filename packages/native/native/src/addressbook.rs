@@ -1,14 +1,17 @@
 use std::str::FromStr;
 
 use neon::prelude::*;
+use sha3::{Digest, Keccak256};
 use uuid::Uuid;
 
-use access::{VaultConfig, WrappedVault};
+use access::{VaultConfig, WrappedVault, args_get_str};
+use emerald_vault::util::optional::none_if_empty;
 use emerald_vault::{
     Address,
     core::chains::Blockchain,
     storage::{
         addressbook::AddressBookmark,
+        error::VaultError,
         vault::VaultAccess
     },
     structs::{
@@ -16,28 +19,445 @@ use emerald_vault::{
         book::BookmarkDetails
     },
 };
-use json::StatusResult;
+use json::{StatusResult, deserialize_some, status_code};
+use meta::{now_millis, BookmarkMeta, EnsMeta, VaultMeta};
+use std::collections::HashMap;
 use std::convert::TryFrom;
 
 #[derive(Serialize, Clone)]
 struct AddressBookmarkJson {
+    pub id: String,
     pub address: String,
     pub name: Option<String>,
     pub description: Option<String>,
-    pub blockchain: u32
+    pub blockchain: u32,
+    pub tags: Vec<String>,
+    pub metadata: HashMap<String, String>,
+    pub ens: Option<EnsMeta>
 }
 
 #[derive(Deserialize, Debug)]
 pub struct NewAddressBookItem {
     pub name: Option<String>,
     pub description: Option<String>,
-    pub address: Address,
-    pub blockchain: u32
+    /// Address as entered by user, a mixed-case address must have a valid checksum
+    pub address: String,
+    pub blockchain: u32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// ENS name resolved to the address by the caller
+    #[serde(default)]
+    pub ens: Option<EnsItemJson>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnsItemJson {
+    pub name: String,
+    /// When the name was resolved, milliseconds since epoch. Current time if not set
+    #[serde(rename = "resolvedAt")]
+    pub resolved_at: Option<u64>
+}
+
+impl EnsItemJson {
+    /// Validated ENS name, or `None` if it's not a valid name
+    fn into_meta(self) -> Option<EnsMeta> {
+        let name = clean_ens_name(self.name.as_str())?;
+        Some(EnsMeta {
+            name,
+            resolved_at: self.resolved_at.unwrap_or_else(now_millis)
+        })
+    }
+}
+
+/// ENS name in lowercase, if it's a name with a top-level domain like `alice.eth`
+fn clean_ens_name(name: &str) -> Option<String> {
+    let name = name.trim().to_lowercase();
+    let valid = name.contains('.')
+        && !name.chars().any(|c| c.is_whitespace())
+        && name.split('.').all(|label| !label.is_empty());
+    if valid {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Trims tags and removes empty and repeated ones, keeping the original order
+fn clean_tags(tags: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !result.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            result.push(tag);
+        }
+    }
+    result
+}
+
+/// Item of an exported Address Book, with all its details. Fields added after the first version are optional, so
+/// older exports can be imported too
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AddressBookRowJson {
+    pub blockchain: u32,
+    pub address: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    #[serde(default)]
+    pub ens: Option<EnsItemJson>
+}
+
+/// Same item as a CSV row, which has only plain values. Tags are separated by `;`, and metadata is a JSON object
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct AddressBookRowCsv {
+    pub blockchain: u32,
+    pub address: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<String>,
+    pub metadata: Option<String>,
+    pub ens: Option<String>,
+    #[serde(rename = "ensResolvedAt")]
+    pub ens_resolved_at: Option<u64>
+}
+
+const CSV_TAG_SEPARATOR: &str = ";";
+
+impl AddressBookRowCsv {
+    fn from_row(row: AddressBookRowJson) -> Result<Self, VaultError> {
+        let metadata = if row.metadata.is_empty() {
+            None
+        } else {
+            let json = serde_json::to_string(&row.metadata)
+                .map_err(|_| VaultError::InvalidDataError("Invalid metadata".to_string()))?;
+            Some(json)
+        };
+        let tags = if row.tags.is_empty() {
+            None
+        } else {
+            Some(row.tags.join(CSV_TAG_SEPARATOR))
+        };
+        Ok(AddressBookRowCsv {
+            blockchain: row.blockchain,
+            address: row.address,
+            name: row.name,
+            description: row.description,
+            tags,
+            metadata,
+            ens_resolved_at: row.ens.as_ref().and_then(|ens| ens.resolved_at),
+            ens: row.ens.map(|ens| ens.name)
+        })
+    }
+
+    /// Item with the details parsed from the plain values, or `None` if the metadata is not a valid JSON object
+    fn into_row(self) -> Option<AddressBookRowJson> {
+        let metadata = match self.metadata {
+            Some(json) => serde_json::from_str::<HashMap<String, String>>(json.as_str()).ok()?,
+            None => HashMap::new()
+        };
+        let tags = match self.tags {
+            Some(tags) => tags.split(CSV_TAG_SEPARATOR).map(|t| t.to_string()).collect(),
+            None => vec![]
+        };
+        let resolved_at = self.ens_resolved_at;
+        Some(AddressBookRowJson {
+            blockchain: self.blockchain,
+            address: self.address,
+            name: self.name,
+            description: self.description,
+            tags,
+            metadata,
+            ens: self.ens.map(|name| EnsItemJson { name, resolved_at })
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BookFormat {
+    Csv,
+    Json
+}
+
+impl FromStr for BookFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(BookFormat::Csv),
+            "json" => Ok(BookFormat::Json),
+            _ => Err(())
+        }
+    }
+}
+
+fn parse_format(value: &str) -> Result<BookFormat, VaultError> {
+    BookFormat::from_str(value)
+        .map_err(|_| VaultError::InvalidDataError(format!("Invalid format: {}", value)))
+}
+
+/// How imported items are added to the Address Book
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    /// Keep existing items, and add or merge the imported ones the same way as `addrbook_add`
+    Merge,
+    /// Remove all existing items before the import
+    Replace
+}
+
+impl FromStr for ImportMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "merge" => Ok(ImportMode::Merge),
+            "replace" => Ok(ImportMode::Replace),
+            _ => Err(())
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportRowJson {
+    /// Number of the imported item, starting from 1 and not counting the CSV header
+    pub row: usize,
+    #[serde(flatten)]
+    pub status: AddResultJson
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportReportJson {
+    pub rows: Vec<ImportRowJson>,
+    pub created: usize,
+    pub merged: usize,
+    pub rejected: usize,
+    /// Items removed before the import in the `replace` mode
+    pub removed: usize
+}
+
+impl From<AddressBookRowJson> for NewAddressBookItem {
+    fn from(value: AddressBookRowJson) -> Self {
+        NewAddressBookItem {
+            name: value.name,
+            description: value.description,
+            address: value.address,
+            blockchain: value.blockchain,
+            tags: value.tags,
+            metadata: value.metadata,
+            ens: value.ens
+        }
+    }
+}
+
+impl AddressBookRowJson {
+    fn new(value: &AddressBookmark, meta: BookmarkMeta) -> Self {
+        AddressBookRowJson {
+            blockchain: value.details.blockchain as u32,
+            address: match &value.details.address {
+                AddressRef::EthereumAddress(address) => address.to_string()
+            },
+            name: value.details.label.clone(),
+            description: value.details.description.clone(),
+            tags: meta.tags,
+            metadata: meta.metadata,
+            ens: meta.ens.map(|ens| EnsItemJson { name: ens.name, resolved_at: Some(ens.resolved_at) })
+        }
+    }
+}
+
+/// Parses each item separately, so an invalid item doesn't prevent the import of the others. Fails only if the data
+/// is not a CSV with a header or a JSON array
+/// Address and ENS details of a new item, or why the item is rejected
+fn check_new_item(item: &NewAddressBookItem) -> Result<(Address, Option<EnsMeta>), &'static str> {
+    let address = Address::from_str(item.address.as_str())
+        .map_err(|_| "invalid-address")?;
+    if !is_valid_checksum(item.address.as_str()) {
+        return Err("invalid-checksum")
+    }
+    let ens = match item.ens.clone() {
+        Some(ens) => Some(ens.into_meta().ok_or("invalid-ens-name")?),
+        None => None
+    };
+    Ok((address, ens))
+}
+
+fn parse_rows(data: &str, format: BookFormat) -> Result<Vec<Option<AddressBookRowJson>>, VaultError> {
+    match format {
+        BookFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .flexible(true)
+                .from_reader(data.as_bytes());
+            reader.headers()
+                .map_err(|_| VaultError::InvalidDataError("Invalid CSV header".to_string()))?;
+            Ok(reader.deserialize::<AddressBookRowCsv>()
+                .map(|row| row.ok().and_then(|row| row.into_row()))
+                .collect())
+        },
+        BookFormat::Json => {
+            let items = serde_json::from_str::<Vec<serde_json::Value>>(data)
+                .map_err(|_| VaultError::InvalidDataError("Invalid JSON".to_string()))?;
+            Ok(items.into_iter()
+                .map(|item| serde_json::from_value::<AddressBookRowJson>(item).ok())
+                .collect())
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub enum AddResultType {
+    #[serde(rename = "created")]
+    Created,
+    /// Same address is already in the Address Book, and the new item added missing details to it
+    #[serde(rename = "merged")]
+    Merged,
+    #[serde(rename = "rejected")]
+    Rejected
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AddResultJson {
+    pub result: AddResultType,
+    /// Id of the created or merged item
+    pub id: Option<String>,
+    /// Why the item is rejected or merged, one of `invalid-address`, `invalid-checksum`, `invalid-ens-name` or
+    /// `duplicate`. An imported item may also be rejected as `invalid-row` or `invalid-blockchain`
+    pub reason: Option<String>
+}
+
+impl AddResultJson {
+    fn created(id: Uuid) -> AddResultJson {
+        AddResultJson { result: AddResultType::Created, id: Some(id.to_string()), reason: None }
+    }
+
+    fn merged(id: Uuid) -> AddResultJson {
+        AddResultJson { result: AddResultType::Merged, id: Some(id.to_string()), reason: Some("duplicate".to_string()) }
+    }
+
+    fn rejected(id: Option<Uuid>, reason: &str) -> AddResultJson {
+        AddResultJson { result: AddResultType::Rejected, id: id.map(|id| id.to_string()), reason: Some(reason.to_string()) }
+    }
+}
+
+/// Verifies the EIP-55 checksum of a mixed-case address. An address in a single case has no checksum and is always
+/// valid
+fn is_valid_checksum(address: &str) -> bool {
+    let hex = address.trim_start_matches("0x");
+    if hex == hex.to_lowercase() || hex == hex.to_uppercase() {
+        return true
+    }
+    let lower = hex.to_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+    hex.chars().enumerate().all(|(i, c)| {
+        let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        if c.is_ascii_digit() {
+            true
+        } else if nibble >= 8 {
+            c.is_ascii_uppercase()
+        } else {
+            c.is_ascii_lowercase()
+        }
+    })
+}
+
+/// Filter of `addrbook_list`, all fields are optional
+#[derive(Deserialize, Debug, Default)]
+pub struct AddressBookFilter {
+    pub blockchain: Option<u32>,
+    /// Case-insensitive text to find in the name, description or address
+    pub query: Option<String>,
+    /// Case-insensitive tag which the item must have
+    pub tag: Option<String>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>
+}
+
+#[derive(Serialize, Clone)]
+struct AddressBookPageJson {
+    pub items: Vec<AddressBookmarkJson>,
+    /// Number of items matching the filter, without offset and limit
+    pub total: usize
+}
+
+impl AddressBookFilter {
+    fn matches(&self, bookmark: &AddressBookmark, meta: &VaultMeta) -> bool {
+        if let Some(blockchain) = self.blockchain {
+            if bookmark.details.blockchain as u32 != blockchain {
+                return false
+            }
+        }
+        if let Some(tag) = &self.tag {
+            let tag = tag.trim().to_lowercase();
+            let tags = meta.get_bookmark(&bookmark.id).tags;
+            if !tags.iter().any(|t| t.to_lowercase() == tag) {
+                return false
+            }
+        }
+        let query = match &self.query {
+            Some(query) if !query.trim().is_empty() => query.trim().to_lowercase(),
+            _ => return true
+        };
+        let details = &bookmark.details;
+        let address = match &details.address {
+            AddressRef::EthereumAddress(address) => address.to_string()
+        };
+        let contains = |value: &Option<String>| value.as_ref()
+            .map(|v| v.to_lowercase().contains(&query))
+            .unwrap_or(false);
+        contains(&details.label) || contains(&details.description) || address.to_lowercase().contains(&query)
+    }
+}
+
+/// Changes of an existing item. A missing field keeps the current value, `null` or an empty string clears it
+#[derive(Deserialize, Debug)]
+pub struct AddressBookItemPatch {
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub description: Option<Option<String>>,
+    /// New address, validated the same way as the address of a new item
+    pub address: Option<String>,
+    /// Replaces all tags
+    pub tags: Option<Vec<String>>,
+    /// Replaces the whole metadata
+    pub metadata: Option<HashMap<String, String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub ens: Option<Option<EnsItemJson>>
+}
+
+impl AddressBookItemPatch {
+    /// Applies all changes except the address, which is validated and set by `update_addressbook`
+    fn apply(self, details: &mut BookmarkDetails, meta: &mut BookmarkMeta) -> Result<(), VaultError> {
+        if let Some(ens) = self.ens {
+            meta.ens = match ens {
+                Some(ens) => Some(ens.into_meta().ok_or_else(|| VaultError::InvalidDataError("ens".to_string()))?),
+                None => None
+            };
+        }
+        if let Some(tags) = self.tags {
+            meta.tags = clean_tags(tags);
+        }
+        if let Some(metadata) = self.metadata {
+            meta.metadata = metadata;
+        }
+        if let Some(name) = self.name {
+            details.label = name.and_then(|s| none_if_empty(s.as_str()));
+        }
+        if let Some(description) = self.description {
+            details.description = description.and_then(|s| none_if_empty(s.as_str()));
+        }
+        Ok(())
+    }
 }
 
-impl From<&AddressBookmark> for AddressBookmarkJson {
-    fn from(value: &AddressBookmark) -> Self {
+impl AddressBookmarkJson {
+    fn new(value: &AddressBookmark, meta: BookmarkMeta) -> Self {
         AddressBookmarkJson {
+            id: value.id.to_string(),
             address: match &value.details.address {
                 AddressRef::EthereumAddress(address) => {
                     address.to_string()
@@ -45,20 +465,23 @@ impl From<&AddressBookmark> for AddressBookmarkJson {
             },
             name: value.details.label.clone(),
             description: value.details.description.clone(),
-            blockchain: value.details.blockchain as u32
+            blockchain: value.details.blockchain as u32,
+            tags: meta.tags,
+            metadata: meta.metadata,
+            ens: meta.ens
         }
     }
 }
 
 impl NewAddressBookItem {
-    pub fn into_bookmark(self, blockchain: Blockchain) -> AddressBookmark {
+    pub fn into_bookmark(self, blockchain: Blockchain, address: Address) -> AddressBookmark {
         AddressBookmark {
             id: Uuid::new_v4(),
             details: BookmarkDetails {
                 blockchain,
-                label: self.name,
-                description: self.description,
-                address: AddressRef::EthereumAddress(self.address)
+                label: self.name.and_then(|s| none_if_empty(s.as_str())),
+                description: self.description.and_then(|s| none_if_empty(s.as_str())),
+                address: AddressRef::EthereumAddress(address)
             }
         }
     }
@@ -67,57 +490,308 @@ impl NewAddressBookItem {
 impl WrappedVault {
     fn list_addressbook(&self) -> Vec<AddressBookmark> {
         let storage = &self.cfg.get_storage();
-        let all = storage.addressbook().get_all().expect("Addressbook unavailable");
+        storage.addressbook().get_all().expect("Addressbook unavailable")
+    }
 
-        let for_chain = all.iter()
-            .map(|b| b.clone())
+    /// Items matching the filter, limited to the requested page, and the total number of matching items
+    fn query_addressbook(&self, filter: &AddressBookFilter) -> Result<(Vec<AddressBookmark>, usize), VaultError> {
+        let meta = self.read_meta()?;
+        let matching: Vec<AddressBookmark> = self.list_addressbook().into_iter()
+            .filter(|b| filter.matches(b, &meta))
+            .collect();
+        let total = matching.len();
+        let page = matching.into_iter()
+            .skip(filter.offset.unwrap_or(0))
+            .take(filter.limit.unwrap_or(usize::max_value()))
             .collect();
+        Ok((page, total))
+    }
+
+    /// Adds the item, unless it has an invalid address or the same address is already on the blockchain. In the
+    /// latter case the existing item gets the name and description from the new item if it doesn't have them, and
+    /// the new tags and metadata keys
+    fn add_to_addressbook(&self, item: NewAddressBookItem) -> Result<AddResultJson, VaultError> {
+        let storage = &self.cfg.get_storage();
+        let blockchain = Blockchain::try_from(item.blockchain)
+            .map_err(|_| VaultError::InvalidDataError("blockchain".to_string()))?;
+        let (address, ens) = match check_new_item(&item) {
+            Ok(checked) => checked,
+            Err(reason) => return Ok(AddResultJson::rejected(None, reason))
+        };
+
+        let existing = self.list_addressbook().into_iter().find(|b| {
+            b.details.blockchain == blockchain && match b.details.address {
+                AddressRef::EthereumAddress(a) => a == address
+            }
+        });
+        let new_meta = BookmarkMeta {
+            tags: clean_tags(item.tags.clone()),
+            metadata: item.metadata.clone(),
+            ens
+        };
+        let bookmark = item.into_bookmark(blockchain, address);
+        let mut existing = match existing {
+            Some(existing) => existing,
+            None => {
+                let id = bookmark.id;
+                storage.addressbook().add(bookmark)?;
+                self.update_meta(|m| m.set_bookmark(id, new_meta))?;
+                return Ok(AddResultJson::created(id))
+            }
+        };
+
+        let mut meta = self.read_meta()?.get_bookmark(&existing.id);
+        let mut meta_changed = false;
+        for tag in new_meta.tags {
+            if !meta.tags.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+                meta.tags.push(tag);
+                meta_changed = true;
+            }
+        }
+        for (key, value) in new_meta.metadata {
+            if !meta.metadata.contains_key(&key) {
+                meta.metadata.insert(key, value);
+                meta_changed = true;
+            }
+        }
+        if meta.ens.is_none() && new_meta.ens.is_some() {
+            meta.ens = new_meta.ens;
+            meta_changed = true;
+        }
 
-        for_chain
+        let mut changed = false;
+        if existing.details.label.is_none() && bookmark.details.label.is_some() {
+            existing.details.label = bookmark.details.label;
+            changed = true;
+        }
+        if existing.details.description.is_none() && bookmark.details.description.is_some() {
+            existing.details.description = bookmark.details.description;
+            changed = true;
+        }
+        if !changed && !meta_changed {
+            return Ok(AddResultJson::rejected(Some(existing.id), "duplicate"))
+        }
+        let id = existing.id;
+        if changed {
+            storage.addressbook().remove(&id)?;
+            storage.addressbook().add(existing)?;
+        }
+        if meta_changed {
+            self.update_meta(|m| m.set_bookmark(id, meta))?;
+        }
+        Ok(AddResultJson::merged(id))
     }
 
-    fn add_to_addressbook(&self, item: NewAddressBookItem) -> bool {
+    /// Changes the item in place, keeping its id. Returns `false` if there is no item with the id. A new address is
+    /// rejected with the same reasons as for a new item, i.e. `invalid-address`, `invalid-checksum` or `duplicate`
+    /// if another item has the same address on the blockchain
+    fn update_addressbook(&self, id: Uuid, patch: AddressBookItemPatch) -> Result<bool, VaultError> {
         let storage = &self.cfg.get_storage();
-        let blockchain = Blockchain::try_from(item.blockchain).expect("Invalid blockchain id");
-        storage.addressbook().add(item.into_bookmark(blockchain)).is_ok()
+        let all = self.list_addressbook();
+        let original = match all.iter().find(|b| b.id == id) {
+            Some(bookmark) => bookmark.clone(),
+            None => return Ok(false)
+        };
+        let mut bookmark = original.clone();
+        if let Some(value) = patch.address.as_ref() {
+            let blockchain = bookmark.details.blockchain;
+            let address = Address::from_str(value.as_str())
+                .map_err(|_| VaultError::InvalidDataError("invalid-address".to_string()))?;
+            if !is_valid_checksum(value.as_str()) {
+                return Err(VaultError::InvalidDataError("invalid-checksum".to_string()))
+            }
+            let duplicate = all.iter().any(|b| {
+                b.id != id && b.details.blockchain == blockchain && match b.details.address {
+                    AddressRef::EthereumAddress(a) => a == address
+                }
+            });
+            if duplicate {
+                return Err(VaultError::InvalidDataError("duplicate".to_string()))
+            }
+            bookmark.details.address = AddressRef::EthereumAddress(address);
+        }
+        let mut meta = self.read_meta()?.get_bookmark(&id);
+        patch.apply(&mut bookmark.details, &mut meta)?;
+        storage.addressbook().remove(&id)?;
+        if let Err(e) = storage.addressbook().add(bookmark) {
+            // put back the original, otherwise the item would be lost
+            storage.addressbook().add(original)?;
+            return Err(e)
+        }
+        self.update_meta(|m| m.set_bookmark(id, meta))?;
+        Ok(true)
     }
 
-    fn remove_addressbook_by_addr(&self, address: &Address) -> bool {
+    /// All items with their tags, metadata and ENS names
+    fn export_addressbook(&self, format: BookFormat) -> Result<String, VaultError> {
+        let meta = self.read_meta()?;
+        let rows: Vec<AddressBookRowJson> = self.list_addressbook().iter()
+            .map(|b| AddressBookRowJson::new(b, meta.get_bookmark(&b.id)))
+            .collect();
+        match format {
+            BookFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(vec![]);
+                for row in rows {
+                    writer.serialize(AddressBookRowCsv::from_row(row)?)
+                        .map_err(|e| VaultError::InvalidDataError(format!("CSV: {}", e)))?;
+                }
+                let data = writer.into_inner()
+                    .map_err(|e| VaultError::InvalidDataError(format!("CSV: {}", e)))?;
+                String::from_utf8(data)
+                    .map_err(|_| VaultError::InvalidDataError("CSV".to_string()))
+            },
+            BookFormat::Json => serde_json::to_string_pretty(&rows)
+                .map_err(|_| VaultError::InvalidDataError("JSON".to_string()))
+        }
+    }
+
+    fn import_addressbook(&self, data: &str, format: BookFormat, mode: ImportMode) -> Result<ImportReportJson, VaultError> {
+        // parse and check everything before removing existing items, so nothing is lost on invalid data
+        let items: Vec<Result<NewAddressBookItem, &'static str>> = parse_rows(data, format)?.into_iter()
+            .map(|row| match row {
+                Some(row) if Blockchain::try_from(row.blockchain).is_ok() => {
+                    let item = NewAddressBookItem::from(row);
+                    check_new_item(&item).map(|_| item)
+                },
+                Some(_) => Err("invalid-blockchain"),
+                None => Err("invalid-row")
+            })
+            .collect();
         let storage = &self.cfg.get_storage();
 
-        let list = self.list_addressbook();
-        let found = list.iter().find(|x| match x.details.address {
-            AddressRef::EthereumAddress(a) => a == *address
-        });
+        let mut removed = 0;
+        if mode == ImportMode::Replace {
+            let mut removed_ids = vec![];
+            for bookmark in self.list_addressbook() {
+                if storage.addressbook().remove(&bookmark.id)? {
+                    removed += 1;
+                    removed_ids.push(bookmark.id);
+                }
+            }
+            self.update_meta(|m| {
+                for id in &removed_ids {
+                    m.bookmarks.remove(id);
+                }
+            })?;
+        }
 
-        if found.is_some() {
-            match storage.addressbook().remove(&found.unwrap().id) {
-                Ok(r) => r,
-                Err(_) => false
+        let mut report = ImportReportJson {
+            rows: vec![],
+            created: 0,
+            merged: 0,
+            rejected: 0,
+            removed
+        };
+        for (i, item) in items.into_iter().enumerate() {
+            let status = match item {
+                Ok(item) => self.add_to_addressbook(item)?,
+                Err(reason) => AddResultJson::rejected(None, reason)
+            };
+            match status.result {
+                AddResultType::Created => report.created += 1,
+                AddResultType::Merged => report.merged += 1,
+                AddResultType::Rejected => report.rejected += 1
             }
-        } else {
-            false
+            report.rows.push(ImportRowJson { row: i + 1, status });
+        }
+        Ok(report)
+    }
+
+    /// Items with the ENS name, on any blockchain
+    fn find_addressbook_by_name(&self, name: &str) -> Result<Vec<(AddressBookmark, BookmarkMeta)>, VaultError> {
+        let name = match clean_ens_name(name) {
+            Some(name) => name,
+            None => return Ok(vec![])
+        };
+        let meta = self.read_meta()?;
+        let found = self.list_addressbook().into_iter()
+            .map(|b| {
+                let item_meta = meta.get_bookmark(&b.id);
+                (b, item_meta)
+            })
+            .filter(|(_, m)| m.ens.as_ref().map(|ens| ens.name == name).unwrap_or(false))
+            .collect();
+        Ok(found)
+    }
+
+    /// Removes all items with the address on the blockchain, returns how many were removed
+    fn remove_addressbook_by_addr(&self, blockchain: Blockchain, address: &Address) -> Result<usize, VaultError> {
+        let storage = &self.cfg.get_storage();
+
+        let found: Vec<Uuid> = self.list_addressbook().iter()
+            .filter(|x| x.details.blockchain == blockchain)
+            .filter(|x| match x.details.address {
+                AddressRef::EthereumAddress(a) => a == *address
+            })
+            .map(|x| x.id)
+            .collect();
+
+        let mut removed = 0;
+        for id in &found {
+            if storage.addressbook().remove(id)? {
+                removed += 1;
+            }
+        }
+        self.update_meta(|m| {
+            for id in &found {
+                m.bookmarks.remove(id);
+            }
+        })?;
+        Ok(removed)
+    }
+
+    /// Removes the item with the id, returns how many were removed, i.e. `0` if there is no such item
+    fn remove_addressbook_by_id(&self, id: Uuid) -> Result<usize, VaultError> {
+        let storage = &self.cfg.get_storage();
+        let exists = self.list_addressbook().iter().any(|x| x.id == id);
+        if !exists {
+            return Ok(0)
         }
+        let removed = storage.addressbook().remove(&id)?;
+        self.update_meta(|m| {
+            m.bookmarks.remove(&id);
+        })?;
+        Ok(if removed { 1 } else { 0 })
+    }
+}
+
+/// Status with the message of an invalid data error, which tells what is wrong with the input, instead of a generic
+/// vault error
+fn data_status<T>(result: Result<T, VaultError>) -> StatusResult<T> {
+    match result {
+        Err(VaultError::InvalidDataError(msg)) => StatusResult::Error(status_code::VAULT_ERROR, msg),
+        other => StatusResult::from(other)
     }
 }
 
 pub fn list(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
-    let list = vault.list_addressbook();
+    let filter = match args_get_str(&mut cx, 1) {
+        Some(json) => serde_json::from_str::<AddressBookFilter>(json.as_str())
+            .map_err(|_| VaultError::InvalidDataError("Invalid filter JSON".to_string())),
+        None => Ok(AddressBookFilter::default())
+    };
 
-    let result: Vec<AddressBookmarkJson> = list.iter()
-        .map(|b| AddressBookmarkJson::from(b))
-        .collect();
+    let result = filter.and_then(|filter| {
+        let (page, total) = vault.query_addressbook(&filter)?;
+        let meta = vault.read_meta()?;
+        Ok(AddressBookPageJson {
+            items: page.iter()
+                .map(|b| AddressBookmarkJson::new(b, meta.get_bookmark(&b.id)))
+                .collect(),
+            total
+        })
+    });
 
-    let status = StatusResult::Ok(result).as_json();
+    let status = data_status(result).as_json();
     let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
     Ok(js_value.downcast().unwrap())
 }
 
 pub fn add(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let chain_code = cfg.chain.clone();
     let vault = WrappedVault::new(cfg);
 
@@ -128,23 +802,107 @@ pub fn add(mut cx: FunctionContext) -> JsResult<JsObject> {
         .expect("Invalid input JSON");
     let result = vault.add_to_addressbook(item);
 
-    let status = StatusResult::Ok(result).as_json();
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+
+}
+
+pub fn update(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let id = cx.argument::<JsString>(1).expect("Address Book item id not provided").value();
+    let id = Uuid::parse_str(id.as_str()).expect("Invalid Address Book item id");
+    let patch_js = cx.argument::<JsString>(2).expect("Update not provided").value();
+    let patch = serde_json::from_str::<AddressBookItemPatch>(patch_js.as_str())
+        .expect("Invalid input JSON");
+
+    let result = vault.update_addressbook(id, patch);
+
+    // keep the reason why the update is rejected
+    let status = data_status(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn find_by_name(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let name = cx.argument::<JsString>(1).expect("Name not provided").value();
+
+    let result = vault.find_addressbook_by_name(name.as_str())
+        .map(|found| found.into_iter()
+            .map(|(b, meta)| AddressBookmarkJson::new(&b, meta))
+            .collect::<Vec<AddressBookmarkJson>>());
+
+    let status = data_status(result).as_json();
     let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
     Ok(js_value.downcast().unwrap())
+}
+
+pub fn export(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let format = cx.argument::<JsString>(1).expect("Format not provided").value();
+
+    let result = parse_format(format.as_str())
+        .and_then(|format| vault.export_addressbook(format));
+
+    let status = data_status(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn import(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
 
+    let data = cx.argument::<JsString>(1).expect("Data not provided").value();
+    let format = cx.argument::<JsString>(2).expect("Format not provided").value();
+    let mode = match args_get_str(&mut cx, 3) {
+        Some(mode) => ImportMode::from_str(mode.as_str())
+            .map_err(|_| VaultError::InvalidDataError(format!("Invalid import mode: {}", mode))),
+        None => Ok(ImportMode::Merge)
+    };
+
+    let result = parse_format(format.as_str())
+        .and_then(|format| mode.map(|mode| (format, mode)))
+        .and_then(|(format, mode)| vault.import_addressbook(data.as_str(), format, mode));
+
+    let status = data_status(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
 }
 
 pub fn remove(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
-    let chain_code = cfg.chain.clone();
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
-    let address = cx.argument::<JsString>(1).expect("Address no provided").value();
+    let blockchain = cx.argument::<JsNumber>(1).expect("Blockchain not provided").value() as u32;
+    let blockchain = Blockchain::try_from(blockchain).expect("Invalid blockchain id");
+    let address = cx.argument::<JsString>(2).expect("Address no provided").value();
     let address = Address::from_str(address.as_str()).expect("Invalid address");
 
-    let removed = vault.remove_addressbook_by_addr(&address);
+    let removed = vault.remove_addressbook_by_addr(blockchain, &address);
+
+    let status = StatusResult::from(removed).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn remove_by_id(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let id = cx.argument::<JsString>(1).expect("Address Book item id not provided").value();
+    let id = Uuid::parse_str(id.as_str()).expect("Invalid Address Book item id");
+
+    let removed = vault.remove_addressbook_by_id(id);
 
-    let status = StatusResult::Ok(removed).as_json();
+    let status = StatusResult::from(removed).as_json();
     let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
     Ok(js_value.downcast().unwrap())
 }
\ No newline at end of file
@@ -0,0 +1,67 @@
+use sha3::{Digest, Keccak256};
+
+use bitcoin::{
+    network::constants::Network,
+    secp256k1::{PublicKey, Secp256k1},
+    util::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey}
+};
+
+use emerald_vault::{
+    mnemonic::{Language, Mnemonic},
+    Address,
+    PrivateKey,
+    Signature
+};
+use seeds::public_key_to_address;
+
+/// Mnemonics of the emulated devices, one per device in the order they're configured. Well known test seeds
+/// which must never hold real funds
+pub const EMULATOR_MNEMONICS: [&str; 3] = [
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+    "legal winner thank year wave sausage worth useful legal winner thank yellow",
+    "letter advice cage absurd amount doctor acoustic avoid letter advice cage above"
+];
+
+/// Seed of an emulated Hardware Wallet
+pub struct EmulatedSeed {
+    seed: Vec<u8>
+}
+
+impl EmulatedSeed {
+    /// Seed of the device at `index` of `EMULATOR_MNEMONICS`
+    pub fn new(index: usize) -> EmulatedSeed {
+        let phrase = EMULATOR_MNEMONICS.get(index).expect("Too many emulated devices");
+        let mnemonic = Mnemonic::try_from(Language::English, phrase)
+            .expect("Invalid emulator mnemonic");
+        EmulatedSeed {
+            seed: mnemonic.seed(None)
+        }
+    }
+
+    pub fn derive(&self, hd_path: &[u32]) -> Option<ExtendedPrivKey> {
+        let secp = Secp256k1::new();
+        let path: Vec<ChildNumber> = hd_path.iter().map(|i| ChildNumber::from(*i)).collect();
+        ExtendedPrivKey::new_master(Network::Bitcoin, &self.seed)
+            .and_then(|k| k.derive_priv(&secp, &DerivationPath::from(path)))
+            .ok()
+    }
+
+    pub fn get_public_key(&self, hd_path: &[u32]) -> Option<PublicKey> {
+        let key = self.derive(hd_path)?;
+        Some(PublicKey::from_secret_key(&Secp256k1::new(), &key.private_key.key))
+    }
+
+    pub fn get_address(&self, hd_path: &[u32]) -> Option<Address> {
+        self.get_public_key(hd_path).map(|key| public_key_to_address(&key))
+    }
+
+    /// Signs Keccak256 hash of the data, `v` of the signature is `27` or `28`
+    pub fn sign(&self, hd_path: &[u32], data: &[u8]) -> Option<Signature> {
+        let key = self.derive(hd_path)?;
+        let mut pk = [0u8; 32];
+        pk.copy_from_slice(&key.private_key.key[..]);
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&Keccak256::digest(data));
+        PrivateKey(pk).sign_hash(hash).ok()
+    }
+}
@@ -0,0 +1,425 @@
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use neon::prelude::{FunctionContext, JsObject, JsResult};
+use sha3::{Digest, Keccak256};
+use uuid::Uuid;
+
+use emerald_vault::{
+    Address,
+    mnemonic::HDPath,
+    Signature,
+    storage::error::VaultError,
+    structs::seed::{HDPathFingerprint, Seed, SeedSource},
+    Transaction
+};
+use access::{VaultConfig, WrappedVault};
+use json::{StatusResult, status_code};
+use ledger::LedgerKey;
+#[cfg(feature = "hw-emulator")]
+use ledger_emulator::LedgerEmulator;
+use transport::HidTransport;
+use trezor::TrezorKey;
+#[cfg(feature = "hw-emulator")]
+use trezor_emulator::TrezorEmulator;
+
+/// Address used to recognize the seed of a connected device
+const FINGERPRINT_PATH: &str = "m/44'/60'/0'/0/0";
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// Which Hardware Wallets are used, set by `hardware` option of the Vault Config. Emulated devices are set as
+/// a comma-separated list, like `emulator,trezor-emulator:locked`, where each of them has its own seed. Emulators
+/// are available only in a build with `hw-emulator` feature, which is used for tests
+#[derive(Debug, Clone, PartialEq)]
+pub enum HardwareType {
+    Hid,
+    #[cfg(feature = "hw-emulator")]
+    Emulator(Vec<EmulatedDevice>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Vendor {
+    Ledger,
+    Trezor
+}
+
+#[cfg(feature = "hw-emulator")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmulatedDevice {
+    pub vendor: Vendor,
+    pub state: EmulatorState
+}
+
+/// State of an emulated device, to test how the errors are handled. Set as `emulator:<state>` in `hardware`
+/// option of the Vault Config
+#[cfg(feature = "hw-emulator")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmulatorState {
+    /// Unlocked and has Ethereum App opened
+    Ready,
+    /// Locked with PIN
+    Locked,
+    /// Unlocked, but shows the dashboard instead of the Ethereum App. Ledger only
+    NoApp,
+    /// User rejects everything which needs a confirmation on the device, i.e. transactions and displayed addresses
+    Rejecting
+}
+
+#[cfg(feature = "hw-emulator")]
+impl FromStr for EmulatorState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ready" => Ok(EmulatorState::Ready),
+            "locked" => Ok(EmulatorState::Locked),
+            "no-app" => Ok(EmulatorState::NoApp),
+            "reject" => Ok(EmulatorState::Rejecting),
+            _ => Err(())
+        }
+    }
+}
+
+#[cfg(feature = "hw-emulator")]
+impl FromStr for EmulatedDevice {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let vendor = match parts.next() {
+            Some("emulator") => Vendor::Ledger,
+            Some("trezor-emulator") => Vendor::Trezor,
+            _ => return Err(())
+        };
+        let state = match parts.next() {
+            Some(state) => EmulatorState::from_str(state)?,
+            None => EmulatorState::Ready
+        };
+        if vendor == Vendor::Trezor && state == EmulatorState::NoApp {
+            // Trezor has Ethereum support in the firmware
+            return Err(())
+        }
+        Ok(EmulatedDevice { vendor, state })
+    }
+}
+
+impl FromStr for HardwareType {
+    type Err = ();
+
+    #[cfg(feature = "hw-emulator")]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "hid" {
+            return Ok(HardwareType::Hid)
+        }
+        let emulators = s.split(',')
+            .map(|item| EmulatedDevice::from_str(item.trim()))
+            .collect::<Result<Vec<EmulatedDevice>, ()>>()?;
+        Ok(HardwareType::Emulator(emulators))
+    }
+
+    #[cfg(not(feature = "hw-emulator"))]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hid" => Ok(HardwareType::Hid),
+            _ => Err(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HWKeyError {
+    NotConnected,
+    InvalidPath,
+    CommunicationError(String),
+    /// Device is locked with PIN
+    Locked,
+    /// Device is not in the Ethereum App
+    WrongApp,
+    /// User rejected the operation on the device
+    Rejected,
+    /// Device responded with an error code, i.e. a Status Word for Ledger or a Failure code for Trezor
+    DeviceError(u16),
+    /// Details of the seed stored in the vault can't be read
+    VaultError(String)
+}
+
+impl HWKeyError {
+    pub fn status_code(&self) -> u32 {
+        match self {
+            HWKeyError::NotConnected => status_code::HW_NOT_CONNECTED,
+            HWKeyError::Locked => status_code::HW_LOCKED,
+            HWKeyError::WrongApp => status_code::HW_WRONG_APP,
+            HWKeyError::Rejected => status_code::HW_REJECTED,
+            HWKeyError::VaultError(_) => status_code::VAULT_ERROR,
+            _ => status_code::HW_ERROR
+        }
+    }
+}
+
+impl From<HWKeyError> for VaultError {
+    fn from(err: HWKeyError) -> Self {
+        VaultError::UnsupportedDataError(format!("Hardware Wallet error: {:?}", err))
+    }
+}
+
+impl From<VaultError> for HWKeyError {
+    fn from(err: VaultError) -> Self {
+        HWKeyError::VaultError(format!("{:?}", err))
+    }
+}
+
+impl From<hidapi::HidError> for HWKeyError {
+    fn from(err: hidapi::HidError) -> Self {
+        HWKeyError::CommunicationError(format!("{}", err))
+    }
+}
+
+/// State of a device and the app opened on it
+#[derive(Serialize, Clone)]
+pub struct HWAppJson {
+    pub connected: bool,
+    pub locked: bool,
+    /// Name of the opened app, `BOLOS` for the Ledger dashboard, or `Trezor` for a Trezor
+    pub app: Option<String>,
+    pub version: Option<String>,
+    /// If the device is ready to work with Ethereum
+    pub ethereum: bool
+}
+
+impl HWAppJson {
+    pub fn disconnected() -> HWAppJson {
+        HWAppJson {
+            connected: false,
+            locked: false,
+            app: None,
+            version: None,
+            ethereum: false
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct HWAddressJson {
+    pub address: Option<String>,
+    pub confirmed: bool
+}
+
+#[derive(Serialize, Clone)]
+pub struct HWDeviceJson {
+    pub id: String,
+    /// `ledger` or `trezor`
+    pub vendor: String,
+    /// Version of the Ethereum App on a Ledger if it's opened, or version of the Trezor firmware
+    #[serde(rename = "appVersion")]
+    pub app_version: Option<String>
+}
+
+/// Parses HD Path like `m/44'/60'/0'/0/0` into its elements, where hardened elements have the highest bit set
+pub fn parse_hd_path(hd_path: &str) -> Result<Vec<u32>, HWKeyError> {
+    let mut parts = hd_path.split('/');
+    if parts.next() != Some("m") {
+        return Err(HWKeyError::InvalidPath)
+    }
+    let mut elements = vec![];
+    for part in parts {
+        let (value, hardened) = if part.ends_with('\'') {
+            (&part[..part.len() - 1], HARDENED)
+        } else {
+            (part, 0)
+        };
+        let value = u32::from_str(value).map_err(|_| HWKeyError::InvalidPath)?;
+        if value >= HARDENED {
+            return Err(HWKeyError::InvalidPath)
+        }
+        elements.push(value | hardened);
+    }
+    if elements.is_empty() || elements.len() > 10 {
+        return Err(HWKeyError::InvalidPath)
+    }
+    Ok(elements)
+}
+
+/// Formats HD Path elements as a string like `m/44'/60'/0'/0/0`, i.e. the reverse of `parse_hd_path`
+pub fn format_hd_path(hd_path: &[u32]) -> String {
+    let mut result = "m".to_string();
+    for e in hd_path {
+        if e & HARDENED != 0 {
+            result.push_str(format!("/{}'", e & !HARDENED).as_str());
+        } else {
+            result.push_str(format!("/{}", e).as_str());
+        }
+    }
+    result
+}
+
+/// Encodes HD Path as number of elements followed by each of them as u32 big-endian. It's the format expected
+/// by Ledger, and also the format of `HDPath::to_bytes`
+pub fn encode_hd_path(hd_path: &[u32]) -> Vec<u8> {
+    let mut result = vec![hd_path.len() as u8];
+    for e in hd_path {
+        result.extend_from_slice(&[(e >> 24) as u8, (e >> 16) as u8, (e >> 8) as u8, *e as u8]);
+    }
+    result
+}
+
+/// Decodes HD Path encoded by `encode_hd_path`, returns the elements and the size of the encoded path
+pub fn decode_hd_path(data: &[u8]) -> Option<(Vec<u32>, usize)> {
+    let count = *data.get(0)? as usize;
+    let end = 1 + count * 4;
+    let path = data.get(1..end)?.chunks(4)
+        .map(|b| (u32::from(b[0]) << 24) | (u32::from(b[1]) << 16) | (u32::from(b[2]) << 8) | u32::from(b[3]))
+        .collect();
+    Some((path, end))
+}
+
+/// Ethereum support of a Hardware Wallet, whichever vendor it is
+pub trait HWKey {
+    /// Identifies the device between the connected ones
+    fn id(&self) -> String;
+
+    fn vendor(&self) -> Vendor;
+
+    fn is_connected(&self) -> bool;
+
+    /// Version of the Ethereum App, or of the firmware if it has Ethereum support built in
+    fn get_app_version(&self) -> Result<String, HWKeyError>;
+
+    fn get_app_state(&self) -> HWAppJson;
+
+    fn get_address(&self, hd_path: &[u32]) -> Result<Address, HWKeyError>;
+
+    /// Shows address at HD Path on the device screen and returns it if the user confirmed that it's the same
+    fn confirm_address(&self, hd_path: &[u32]) -> Result<Address, HWKeyError>;
+
+    /// Signs the transaction for the specified chain, after it's confirmed by the user. Returned `v` is normalized
+    /// to `27` or `28`, without the Chain Id
+    fn sign_transaction(&self, hd_path: &[u32], tx: &Transaction, chain_id: u8) -> Result<Signature, HWKeyError>;
+
+    /// Address used to recognize the seed of the device, see `address_fingerprint` and `storage_fingerprint`
+    fn fingerprint_address(&self) -> Result<Address, HWKeyError> {
+        self.get_address(&parse_hd_path(FINGERPRINT_PATH)?)
+    }
+
+    /// Identifies the seed of the connected device without storing any of its addresses
+    fn fingerprint(&self) -> Result<String, HWKeyError> {
+        Ok(address_fingerprint(&self.fingerprint_address()?))
+    }
+}
+
+/// Fingerprint kept in the module metadata, a hash of the address at `FINGERPRINT_PATH`
+pub fn address_fingerprint(address: &Address) -> String {
+    let hash = Keccak256::digest(address.to_string().as_bytes());
+    hex::encode(hash)
+}
+
+/// Fingerprint kept with a Ledger seed in the vault storage, for the same address as `address_fingerprint`
+pub fn storage_fingerprint(address: &Address) -> Result<HDPathFingerprint, HWKeyError> {
+    let hd_path = HDPath::try_from(FINGERPRINT_PATH).map_err(|_| HWKeyError::InvalidPath)?;
+    Ok(HDPathFingerprint::from_address(hd_path, address))
+}
+
+impl WrappedVault {
+    /// All connected devices, Ledgers first, each ordered by its id
+    pub fn list_hw_keys(&self) -> Vec<Box<dyn HWKey>> {
+        match &self.cfg.hardware {
+            HardwareType::Hid => {
+                let ledgers = HidTransport::list(Vendor::Ledger)
+                    .unwrap_or_else(|_| vec![])
+                    .into_iter()
+                    .map(|t| Box::new(LedgerKey::new(Box::new(t))) as Box<dyn HWKey>);
+                let trezors = HidTransport::list(Vendor::Trezor)
+                    .unwrap_or_else(|_| vec![])
+                    .into_iter()
+                    .map(|t| Box::new(TrezorKey::new(Box::new(t))) as Box<dyn HWKey>);
+                ledgers.chain(trezors).collect()
+            },
+            #[cfg(feature = "hw-emulator")]
+            HardwareType::Emulator(devices) => devices.iter()
+                .enumerate()
+                .map(|(i, device)| match device.vendor {
+                    Vendor::Ledger => Box::new(LedgerKey::new(Box::new(LedgerEmulator::new(i, device.state)))) as Box<dyn HWKey>,
+                    Vendor::Trezor => Box::new(TrezorKey::new(Box::new(TrezorEmulator::new(i, device.state)))) as Box<dyn HWKey>
+                })
+                .collect()
+        }
+    }
+
+    /// Device with the specified id, or the first connected device if it's not specified
+    pub fn get_hw_key(&self, device: Option<String>) -> Result<Box<dyn HWKey>, HWKeyError> {
+        let mut all = self.list_hw_keys();
+        let pos = match device {
+            Some(id) => all.iter().position(|k| k.id() == id),
+            None => if all.is_empty() { None } else { Some(0) }
+        };
+        pos.map(|pos| all.remove(pos)).ok_or(HWKeyError::NotConnected)
+    }
+
+    /// Device which has the seed, i.e. has the same fingerprint. The fingerprint is taken from the metadata, or
+    /// from the seed in the vault if it's not there. Seeds without a fingerprint match the first device
+    pub fn get_hw_key_for_seed(&self, seed_id: &Uuid) -> Result<Box<dyn HWKey>, HWKeyError> {
+        let expected = match self.read_meta()?.get_seed(seed_id).fingerprint {
+            Some(fingerprint) => fingerprint,
+            None => return self.get_hw_key_by_storage_fingerprint(seed_id)
+        };
+        // if none matches, report the state of a device which didn't respond, as it may be the right one
+        let mut error = HWKeyError::NotConnected;
+        for key in self.list_hw_keys() {
+            match key.fingerprint() {
+                Ok(actual) => if actual == expected {
+                    return Ok(key)
+                },
+                Err(e) => error = e
+            }
+        }
+        Err(error)
+    }
+
+    /// Device which has any of the fingerprints of a Ledger seed stored in the vault
+    fn get_hw_key_by_storage_fingerprint(&self, seed_id: &Uuid) -> Result<Box<dyn HWKey>, HWKeyError> {
+        let expected = match self.cfg.get_storage().seeds().get(*seed_id) {
+            Ok(Seed { source: SeedSource::Ledger(ledger), .. }) => ledger.fingerprints,
+            _ => vec![]
+        };
+        if expected.is_empty() {
+            return self.get_hw_key(None)
+        }
+        let mut error = HWKeyError::NotConnected;
+        for key in self.list_hw_keys() {
+            match key.fingerprint_address().and_then(|address| storage_fingerprint(&address)) {
+                Ok(actual) => if expected.contains(&actual) {
+                    return Ok(key)
+                },
+                Err(e) => error = e
+            }
+        }
+        Err(error)
+    }
+
+    /// Fingerprints of all connected devices which respond
+    pub fn get_connected_fingerprints(&self) -> Vec<String> {
+        self.list_hw_keys().iter()
+            .filter_map(|k| k.fingerprint().ok())
+            .collect()
+    }
+}
+
+pub fn list_devices(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let result: Vec<HWDeviceJson> = vault.list_hw_keys().iter()
+        .map(|k| HWDeviceJson {
+            id: k.id(),
+            vendor: match k.vendor() {
+                Vendor::Ledger => "ledger".to_string(),
+                Vendor::Trezor => "trezor".to_string()
+            },
+            app_version: k.get_app_version().ok()
+        })
+        .collect();
+
+    let status = StatusResult::Ok(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
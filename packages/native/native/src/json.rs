@@ -1,4 +1,5 @@
 use neon::prelude::{Context, Handle, JsObject, JsValue, Object, Value};
+use serde::{Deserialize, Deserializer};
 
 use emerald_vault::{
     storage::{
@@ -17,6 +18,26 @@ impl std::convert::From<hex::FromHexError> for JsonError {
     }
 }
 
+/// Deserializes a present field as `Some`, including `null` which becomes `Some(None)`. Used with
+/// `#[serde(default)]` to tell a missing field of a patch, which keeps the current value, from `null` which clears it
+pub fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where T: Deserialize<'de>, D: Deserializer<'de> {
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Error codes passed to JS as `error.code`, must be in sync with `StatusCode` in types.ts
+pub mod status_code {
+    pub const UNKNOWN: u32 = 0;
+    pub const NOT_IMPLEMENTED: u32 = 1;
+    pub const VAULT_ERROR: u32 = 2;
+    pub const WATCH_ONLY: u32 = 3;
+    pub const HW_ERROR: u32 = 4;
+    pub const HW_NOT_CONNECTED: u32 = 5;
+    pub const HW_LOCKED: u32 = 6;
+    pub const HW_WRONG_APP: u32 = 7;
+    pub const HW_REJECTED: u32 = 8;
+}
+
 #[derive(Serialize)]
 pub struct StatusErrorJson {
     pub code: u32,
@@ -35,6 +56,15 @@ pub enum StatusResult<T> {
     Error(u32, String)
 }
 
+impl <T> StatusResult<T> {
+    pub fn map<R, F>(self, f: F) -> StatusResult<R> where F: FnOnce(T) -> R {
+        match self {
+            StatusResult::Ok(t) => StatusResult::Ok(f(t)),
+            StatusResult::Error(code, message) => StatusResult::Error(code, message)
+        }
+    }
+}
+
 impl <T> StatusResult<T> where T: Clone {
     pub fn as_json(&self) -> StatusJson<T> {
         match self {
@@ -59,7 +89,7 @@ impl <T> From<Result<T, VaultError>> for StatusResult<T> {
     fn from(r: Result<T, VaultError>) -> Self {
         match r {
             Ok(t) => StatusResult::Ok(t),
-            Err(e) => StatusResult::Error(2, "Vault Error".to_string())
+            Err(e) => StatusResult::Error(status_code::VAULT_ERROR, "Vault Error".to_string())
         }
     }
 }
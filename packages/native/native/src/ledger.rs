@@ -0,0 +1,169 @@
+use std::str::FromStr;
+
+use emerald_vault::{Address, Signature, Transaction};
+use hardware::{encode_hd_path, HWAppJson, HWKey, HWKeyError, Vendor};
+use transport::{Apdu, HwTransport};
+
+/// Name of the "app" which is the dashboard of a Ledger
+pub const DASHBOARD_NAME: &str = "BOLOS";
+
+/// Commands processed by the device itself, whichever app is opened
+pub const CLA_BOLOS: u8 = 0xb0;
+pub const INS_GET_APP_AND_VERSION: u8 = 0x01;
+
+/// Commands of the Ethereum App
+pub const CLA_ETHEREUM: u8 = 0xe0;
+pub const INS_GET_ADDRESS: u8 = 0x02;
+pub const P1_NO_DISPLAY: u8 = 0x00;
+pub const P1_DISPLAY: u8 = 0x01;
+pub const INS_SIGN: u8 = 0x04;
+pub const INS_GET_APP_CONFIGURATION: u8 = 0x06;
+pub const P1_SIGN_FIRST: u8 = 0x00;
+pub const P1_SIGN_MORE: u8 = 0x80;
+
+/// Max size of data in a single APDU
+const APDU_CHUNK_SIZE: usize = 255;
+
+/// Ethereum App on a Ledger, accessed through a transport
+pub struct LedgerKey {
+    transport: Box<dyn HwTransport>
+}
+
+impl LedgerKey {
+    pub fn new(transport: Box<dyn HwTransport>) -> LedgerKey {
+        LedgerKey { transport }
+    }
+
+    /// Name and version of the opened app. For the dashboard it's `BOLOS` and version of the firmware
+    fn get_app(&self) -> Result<(String, String), HWKeyError> {
+        let apdu = Apdu {
+            cla: CLA_BOLOS,
+            ins: INS_GET_APP_AND_VERSION,
+            p1: 0x00,
+            p2: 0x00,
+            data: vec![]
+        };
+        let response = match self.transport.exchange(&apdu) {
+            Ok(response) => response,
+            // an older firmware doesn't support the command, but the Ethereum App may still respond
+            Err(HWKeyError::WrongApp) => return self.get_app_version().map(|v| ("Ethereum".to_string(), v)),
+            Err(e) => return Err(e)
+        };
+        let invalid = || HWKeyError::CommunicationError("Invalid app response".to_string());
+        let name_len = *response.get(1).ok_or_else(invalid)? as usize;
+        let name = response.get(2..2 + name_len).ok_or_else(invalid)?;
+        let version_len = *response.get(2 + name_len).ok_or_else(invalid)? as usize;
+        let version_start = 3 + name_len;
+        let version = response.get(version_start..version_start + version_len).ok_or_else(invalid)?;
+        Ok((String::from_utf8_lossy(name).to_string(), String::from_utf8_lossy(version).to_string()))
+    }
+
+    fn request_address(&self, hd_path: &[u32], p1: u8) -> Result<Address, HWKeyError> {
+        let apdu = Apdu {
+            cla: CLA_ETHEREUM,
+            ins: INS_GET_ADDRESS,
+            p1,
+            p2: 0x00,
+            data: encode_hd_path(hd_path)
+        };
+        let response = self.transport.exchange(&apdu)?;
+        let invalid = || HWKeyError::CommunicationError("Invalid address response".to_string());
+        let pk_len = *response.get(0).ok_or_else(invalid)? as usize;
+        let addr_len = *response.get(1 + pk_len).ok_or_else(invalid)? as usize;
+        let addr_start = 2 + pk_len;
+        let address = response.get(addr_start..addr_start + addr_len).ok_or_else(invalid)?;
+        let address = String::from_utf8(address.to_vec()).map_err(|_| invalid())?;
+        Address::from_str(format!("0x{}", address).as_str()).map_err(|_| invalid())
+    }
+}
+
+impl HWKey for LedgerKey {
+    fn id(&self) -> String {
+        self.transport.id()
+    }
+
+    fn vendor(&self) -> Vendor {
+        Vendor::Ledger
+    }
+
+    fn is_connected(&self) -> bool {
+        self.transport.is_connected()
+    }
+
+    fn get_app_version(&self) -> Result<String, HWKeyError> {
+        let apdu = Apdu {
+            cla: CLA_ETHEREUM,
+            ins: INS_GET_APP_CONFIGURATION,
+            p1: 0x00,
+            p2: 0x00,
+            data: vec![]
+        };
+        let response = self.transport.exchange(&apdu)?;
+        if response.len() < 4 {
+            return Err(HWKeyError::CommunicationError("Invalid configuration response".to_string()))
+        }
+        Ok(format!("{}.{}.{}", response[1], response[2], response[3]))
+    }
+
+    fn get_app_state(&self) -> HWAppJson {
+        if !self.is_connected() {
+            return HWAppJson::disconnected()
+        }
+        let mut result = HWAppJson {
+            connected: true,
+            ..HWAppJson::disconnected()
+        };
+        match self.get_app() {
+            Ok((app, version)) => {
+                result.ethereum = app != DASHBOARD_NAME && self.get_app_version().is_ok();
+                result.app = Some(app);
+                result.version = Some(version);
+            },
+            Err(HWKeyError::Locked) => result.locked = true,
+            Err(HWKeyError::NotConnected) => result.connected = false,
+            Err(_) => {}
+        }
+        result
+    }
+
+    fn get_address(&self, hd_path: &[u32]) -> Result<Address, HWKeyError> {
+        self.request_address(hd_path, P1_NO_DISPLAY)
+    }
+
+    fn confirm_address(&self, hd_path: &[u32]) -> Result<Address, HWKeyError> {
+        self.request_address(hd_path, P1_DISPLAY)
+    }
+
+    /// Sends RLP of the unsigned EIP-155 transaction, prefixed with HD Path, split into chunks
+    fn sign_transaction(&self, hd_path: &[u32], tx: &Transaction, chain_id: u8) -> Result<Signature, HWKeyError> {
+        let mut data = encode_hd_path(hd_path);
+        data.extend_from_slice(&tx.to_rlp(Some(chain_id)));
+        let mut response = vec![];
+        for (i, chunk) in data.chunks(APDU_CHUNK_SIZE).enumerate() {
+            let apdu = Apdu {
+                cla: CLA_ETHEREUM,
+                ins: INS_SIGN,
+                p1: if i == 0 { P1_SIGN_FIRST } else { P1_SIGN_MORE },
+                p2: 0x00,
+                data: chunk.to_vec()
+            };
+            response = self.transport.exchange(&apdu)?;
+        }
+        if response.len() != 65 {
+            return Err(HWKeyError::CommunicationError("Invalid signature response".to_string()))
+        }
+        // the device adds `chain_id * 2 + 35` to the recovery id, and keeps only the lowest byte of it
+        let eip155 = (u32::from(chain_id) * 2 + 35) as u8;
+        let recovery = response[0].wrapping_sub(eip155);
+        let v = match recovery {
+            0 | 1 => 27 + recovery,
+            _ if response[0] == 27 || response[0] == 28 => response[0],
+            _ => return Err(HWKeyError::CommunicationError("Invalid signature V".to_string()))
+        };
+        let mut r = [0u8; 32];
+        r.copy_from_slice(&response[1..33]);
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&response[33..65]);
+        Ok(Signature { v, r, s })
+    }
+}
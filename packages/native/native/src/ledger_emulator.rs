@@ -0,0 +1,221 @@
+use std::cell::RefCell;
+
+use bitcoin::secp256k1::{PublicKey, Secp256k1};
+
+use emerald_vault::trim_hex;
+use emulator::EmulatedSeed;
+use hardware::{decode_hd_path, EmulatorState, HWKeyError};
+use ledger::{
+    CLA_BOLOS,
+    CLA_ETHEREUM,
+    DASHBOARD_NAME,
+    INS_GET_ADDRESS,
+    INS_GET_APP_AND_VERSION,
+    INS_GET_APP_CONFIGURATION,
+    INS_SIGN,
+    P1_DISPLAY,
+    P1_SIGN_FIRST,
+    P1_SIGN_MORE
+};
+use seeds::public_key_to_address;
+use transport::{
+    Apdu,
+    HwTransport,
+    SW_CLA_NOT_SUPPORTED,
+    SW_INS_NOT_SUPPORTED,
+    SW_LOCKED,
+    SW_OK,
+    SW_USER_REJECTED
+};
+
+/// Version of the Ethereum App reported by the emulator
+const APP_VERSION: [u8; 3] = [1, 4, 0];
+const FIRMWARE_VERSION: &str = "1.6.0";
+
+const SW_WRONG_DATA: u16 = 0x6a80;
+
+/// Emulates Ledger with the Ethereum App on the level of APDU commands, with a fixed test seed.
+/// Allows to test the Ledger protocol without a device
+pub struct LedgerEmulator {
+    index: usize,
+    seed: EmulatedSeed,
+    state: EmulatorState,
+    /// HD Path and RLP of a transaction which is sent in several chunks
+    signing: RefCell<Option<(Vec<u32>, Vec<u8>)>>
+}
+
+fn with_status(mut data: Vec<u8>, sw: u16) -> Vec<u8> {
+    data.push((sw >> 8) as u8);
+    data.push(sw as u8);
+    data
+}
+
+fn read_be(data: &[u8]) -> usize {
+    data.iter().fold(0, |acc, b| (acc << 8) | *b as usize)
+}
+
+/// Reads header of an RLP item at the position, returns where its payload starts and the payload length
+fn rlp_item(data: &[u8], pos: usize) -> Option<(usize, usize)> {
+    let prefix = *data.get(pos)?;
+    match prefix {
+        0x00..=0x7f => Some((pos, 1)),
+        0x80..=0xb7 => Some((pos + 1, (prefix - 0x80) as usize)),
+        0xc0..=0xf7 => Some((pos + 1, (prefix - 0xc0) as usize)),
+        _ => {
+            let size = if prefix < 0xc0 { prefix - 0xb7 } else { prefix - 0xf7 };
+            let size = size as usize;
+            let len = read_be(data.get(pos + 1..pos + 1 + size)?);
+            Some((pos + 1 + size, len))
+        }
+    }
+}
+
+/// Chain Id of an unsigned EIP-155 transaction, which is the 7th element of the RLP list
+fn rlp_chain_id(rlp: &[u8]) -> Option<usize> {
+    let (mut pos, _) = rlp_item(rlp, 0)?;
+    for _ in 0..6 {
+        let (start, len) = rlp_item(rlp, pos)?;
+        pos = start + len;
+    }
+    let (start, len) = rlp_item(rlp, pos)?;
+    rlp.get(start..start + len).map(read_be)
+}
+
+impl LedgerEmulator {
+    /// Creates emulator of a device with the seed at `index` of `emulator::EMULATOR_MNEMONICS`
+    pub fn new(index: usize, state: EmulatorState) -> LedgerEmulator {
+        LedgerEmulator {
+            index,
+            seed: EmulatedSeed::new(index),
+            state,
+            signing: RefCell::new(None)
+        }
+    }
+
+    /// Response is: length of the public key, uncompressed public key, length of the address, address as hex string
+    /// without 0x prefix, and the chain code if requested by P2
+    fn get_address(&self, apdu: &Apdu) -> (Vec<u8>, u16) {
+        let key = match decode_hd_path(&apdu.data).and_then(|(path, _)| self.seed.derive(&path)) {
+            Some(key) => key,
+            None => return (vec![], SW_WRONG_DATA)
+        };
+        if apdu.p1 == P1_DISPLAY && self.state == EmulatorState::Rejecting {
+            return (vec![], SW_USER_REJECTED)
+        }
+        let secp = Secp256k1::new();
+        let public = PublicKey::from_secret_key(&secp, &key.private_key.key);
+        let address = public_key_to_address(&public);
+        let address = trim_hex(address.to_string().as_str()).to_string();
+
+        let mut result = vec![];
+        let public = public.serialize_uncompressed();
+        result.push(public.len() as u8);
+        result.extend_from_slice(&public);
+        result.push(address.len() as u8);
+        result.extend_from_slice(address.as_bytes());
+        if apdu.p2 == 0x01 {
+            result.extend_from_slice(&key.chain_code[..]);
+        }
+        (result, SW_OK)
+    }
+
+    /// Transaction RLP is sent in chunks, the first one starts with HD Path. Signature is returned when the whole
+    /// RLP is received, as `v`, `r` and `s` where `v` already includes the EIP-155 Chain Id
+    fn sign(&self, apdu: &Apdu) -> (Vec<u8>, u16) {
+        let mut signing = self.signing.borrow_mut();
+        match apdu.p1 {
+            P1_SIGN_FIRST => match decode_hd_path(&apdu.data) {
+                Some((path, end)) => *signing = Some((path, apdu.data[end..].to_vec())),
+                None => return (vec![], SW_WRONG_DATA)
+            },
+            P1_SIGN_MORE => match signing.as_mut() {
+                Some((_, rlp)) => rlp.extend_from_slice(&apdu.data),
+                None => return (vec![], SW_WRONG_DATA)
+            },
+            _ => return (vec![], SW_WRONG_DATA)
+        }
+        let complete = match signing.as_ref().and_then(|(_, rlp)| rlp_item(rlp, 0).map(|(start, len)| rlp.len() >= start + len)) {
+            Some(complete) => complete,
+            None => return (vec![], SW_WRONG_DATA)
+        };
+        if !complete {
+            return (vec![], SW_OK)
+        }
+        let (path, rlp) = signing.take().unwrap();
+        if self.state == EmulatorState::Rejecting {
+            return (vec![], SW_USER_REJECTED)
+        }
+        let signature = match self.seed.sign(&path, &rlp) {
+            Some(signature) => signature,
+            None => return (vec![], SW_WRONG_DATA)
+        };
+        let v = match rlp_chain_id(&rlp) {
+            // the app returns only the lowest byte for large Chain Ids
+            Some(chain_id) => (chain_id * 2 + 35 + (signature.v as usize - 27)) as u8,
+            None => signature.v
+        };
+        let mut result = vec![v];
+        result.extend_from_slice(&signature.r);
+        result.extend_from_slice(&signature.s);
+        (result, SW_OK)
+    }
+
+    /// Response is flags (arbitrary data enabled) followed by major, minor and patch versions
+    fn get_app_configuration(&self) -> (Vec<u8>, u16) {
+        let mut result = vec![0x01];
+        result.extend_from_slice(&APP_VERSION);
+        (result, SW_OK)
+    }
+
+    /// Response is format (always 1), length of the name, name of the opened app, length of the version, version,
+    /// length of flags, flags
+    fn get_app_and_version(&self) -> (Vec<u8>, u16) {
+        let (name, version) = match self.state {
+            EmulatorState::NoApp => (DASHBOARD_NAME.to_string(), FIRMWARE_VERSION.to_string()),
+            _ => ("Ethereum".to_string(), format!("{}.{}.{}", APP_VERSION[0], APP_VERSION[1], APP_VERSION[2]))
+        };
+        let mut result = vec![0x01, name.len() as u8];
+        result.extend_from_slice(name.as_bytes());
+        result.push(version.len() as u8);
+        result.extend_from_slice(version.as_bytes());
+        result.extend_from_slice(&[0x01, 0x00]);
+        (result, SW_OK)
+    }
+
+    fn process(&self, apdu: &Apdu) -> (Vec<u8>, u16) {
+        if self.state == EmulatorState::Locked {
+            return (vec![], SW_LOCKED)
+        }
+        if apdu.cla == CLA_BOLOS && apdu.ins == INS_GET_APP_AND_VERSION {
+            return self.get_app_and_version()
+        }
+        if self.state == EmulatorState::NoApp {
+            return (vec![], SW_CLA_NOT_SUPPORTED)
+        }
+        if apdu.cla != CLA_ETHEREUM {
+            return (vec![], SW_CLA_NOT_SUPPORTED)
+        }
+        match apdu.ins {
+            INS_GET_ADDRESS => self.get_address(apdu),
+            INS_SIGN => self.sign(apdu),
+            INS_GET_APP_CONFIGURATION => self.get_app_configuration(),
+            _ => (vec![], SW_INS_NOT_SUPPORTED)
+        }
+    }
+}
+
+impl HwTransport for LedgerEmulator {
+    fn id(&self) -> String {
+        format!("emulator:{}", self.index)
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn send(&self, apdu: &[u8]) -> Result<Vec<u8>, HWKeyError> {
+        let apdu = Apdu::from_bytes(apdu)?;
+        let (data, sw) = self.process(&apdu);
+        Ok(with_status(data, sw))
+    }
+}
@@ -1,5 +1,8 @@
+extern crate bitcoin;
+extern crate csv;
 extern crate emerald_vault;
 extern crate hex;
+extern crate hidapi;
 #[macro_use]
 extern crate neon;
 #[macro_use]
@@ -8,6 +11,7 @@ extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
+extern crate sha3;
 extern crate uuid;
 
 use neon::prelude::*;
@@ -20,12 +24,29 @@ mod sign;
 mod addressbook;
 mod wallets;
 mod admin;
+mod meta;
+mod mnemonic;
+mod hardware;
+mod ledger;
+mod transport;
+#[cfg(feature = "hw-emulator")]
+mod emulator;
+#[cfg(feature = "hw-emulator")]
+mod ledger_emulator;
+mod trezor;
+#[cfg(feature = "hw-emulator")]
+mod trezor_emulator;
 
 register_module!(mut cx, {
     cx.export_function("wallets_list", wallets::list).expect("wallets_list not exported");
     cx.export_function("wallets_add", wallets::add).expect("wallets_add not exported");
     cx.export_function("wallets_addAccount", wallets::add_account_to_wallet).expect("wallets_addAccount not exported");
     cx.export_function("wallets_updateLabel", wallets::update_label).expect("wallets_updateLabel not exported");
+    cx.export_function("wallets_update", wallets::update).expect("wallets_update not exported");
+    cx.export_function("wallets_updateAccount", wallets::update_account).expect("wallets_updateAccount not exported");
+    cx.export_function("wallets_moveAccount", wallets::move_account).expect("wallets_moveAccount not exported");
+    cx.export_function("wallets_merge", wallets::merge).expect("wallets_merge not exported");
+    cx.export_function("wallets_split", wallets::split).expect("wallets_split not exported");
     cx.export_function("wallets_removeAccount", wallets::remove_account).expect("wallets_removeAccount not exported");
 
     cx.export_function("accounts_import", accounts::import_ethereum).expect("accounts_import not exported");
@@ -37,13 +58,26 @@ register_module!(mut cx, {
     cx.export_function("addrbook_list", addressbook::list).expect("addrbook_list not exported");
     cx.export_function("addrbook_add", addressbook::add).expect("addrbook_add not exported");
     cx.export_function("addrbook_remove", addressbook::remove).expect("addrbook_remove not exported");
+    cx.export_function("addrbook_update", addressbook::update).expect("addrbook_update not exported");
+    cx.export_function("addrbook_removeById", addressbook::remove_by_id).expect("addrbook_removeById not exported");
+    cx.export_function("addrbook_findByName", addressbook::find_by_name).expect("addrbook_findByName not exported");
+    cx.export_function("addrbook_export", addressbook::export).expect("addrbook_export not exported");
+    cx.export_function("addrbook_import", addressbook::import).expect("addrbook_import not exported");
 
+    cx.export_function("ledger_listDevices", hardware::list_devices).expect("ledger_listDevices not exported");
     cx.export_function("ledger_isConnected", seeds::is_connected).expect("ledger_isConnected not exported");
+    cx.export_function("ledger_getApp", seeds::get_app).expect("ledger_getApp not exported");
+    cx.export_function("ledger_confirmAddress", seeds::confirm_address).expect("ledger_confirmAddress not exported");
     cx.export_function("ledger_listAddresses", seeds::list_addresses).expect("ledger_listAddresses not exported");
 
     cx.export_function("seed_generateMnemonic", seeds::generate_mnemonic).expect("seed_generateMnemonic not exported");
     cx.export_function("seed_add", seeds::add).expect("seed_add not exported");
     cx.export_function("seed_list", seeds::list).expect("seed_list not exported");
+    cx.export_function("seed_updateLabel", seeds::update_label).expect("seed_updateLabel not exported");
+    cx.export_function("seed_remove", seeds::remove).expect("seed_remove not exported");
+    cx.export_function("seed_export", seeds::export).expect("seed_export not exported");
+    cx.export_function("seed_getXpub", seeds::get_xpub).expect("seed_getXpub not exported");
+    cx.export_function("seed_scanAddresses", seeds::scan_addresses).expect("seed_scanAddresses not exported");
 
     cx.export_function("admin_migrate", admin::migrate).expect("admin_migrate not exported");
 
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use uuid::Uuid;
+
+use access::WrappedVault;
+use emerald_vault::storage::error::VaultError;
+
+/// Details which are not part of the Vault storage format, kept by the module in a separate file in the vault dir
+const META_FILE: &str = "node-meta.json";
+/// Exists while the metadata is updated, see `MetaLock`
+const META_LOCK_FILE: &str = "node-meta.json.lock";
+const META_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+/// A lock older than that is left by a process which crashed during update
+const META_LOCK_STALE: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct SeedMeta {
+    pub label: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<u64>,
+    /// Fingerprint of a Ledger seed, to recognize the device when it's connected
+    #[serde(default)]
+    pub fingerprint: Option<String>
+}
+
+/// ENS name of an Address Book item. The name is resolved by the caller, the vault keeps only the result
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnsMeta {
+    pub name: String,
+    /// When the name was resolved to the address of the item, as milliseconds since epoch
+    #[serde(rename = "resolvedAt")]
+    pub resolved_at: u64
+}
+
+/// Details of an Address Book item, in addition to its label and description
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct BookmarkMeta {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    #[serde(default)]
+    pub ens: Option<EnsMeta>
+}
+
+/// Details of an account in a wallet
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct AccountMeta {
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Hidden accounts are not listed unless the vault is opened with `showHidden`
+    #[serde(default)]
+    pub hidden: bool
+}
+
+/// Details of a wallet, in addition to its label
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct WalletMeta {
+    #[serde(default)]
+    pub description: Option<String>,
+    /// UI hint, a color code like `#ff8800`
+    #[serde(default)]
+    pub color: Option<String>,
+    /// UI hint, an icon code
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Not set for a wallet created before the module started to track it
+    #[serde(rename = "createdAt", default)]
+    pub created_at: Option<u64>,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: Option<u64>,
+    /// Position in the list of wallets, wallets without it go after the ordered ones
+    #[serde(default)]
+    pub order: Option<i32>,
+    /// Details of the wallet accounts, by account id
+    #[serde(default)]
+    pub accounts: HashMap<usize, AccountMeta>,
+    /// Id for the next watch-only or Hardware Wallet account, so they don't get an id of a removed account. Accounts
+    /// with a key are added by the vault storage and get the id it gives
+    #[serde(rename = "nextAccountId", default)]
+    pub next_account_id: usize
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct VaultMeta {
+    #[serde(default)]
+    pub seeds: HashMap<Uuid, SeedMeta>,
+    #[serde(default)]
+    pub bookmarks: HashMap<Uuid, BookmarkMeta>,
+    #[serde(default)]
+    pub wallets: HashMap<Uuid, WalletMeta>
+}
+
+impl AccountMeta {
+    pub fn is_empty(&self) -> bool {
+        self.label.is_none() && self.description.is_none() && !self.hidden
+    }
+}
+
+impl WalletMeta {
+    pub fn get_account(&self, id: usize) -> AccountMeta {
+        self.accounts.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// Sets details of an account, or removes them if they're empty
+    pub fn set_account(&mut self, id: usize, meta: AccountMeta) {
+        if meta.is_empty() {
+            self.accounts.remove(&id);
+        } else {
+            self.accounts.insert(id, meta);
+        }
+    }
+}
+
+impl BookmarkMeta {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.metadata.is_empty() && self.ens.is_none()
+    }
+}
+
+/// Current time as milliseconds since epoch, i.e. same as `Date.now()` in JS
+pub fn now_millis() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Invalid system time");
+    now.as_secs() * 1000 + u64::from(now.subsec_millis())
+}
+
+impl VaultMeta {
+    pub fn get_seed(&self, id: &Uuid) -> SeedMeta {
+        self.seeds.get(id).cloned().unwrap_or_default()
+    }
+
+    pub fn get_wallet(&self, id: &Uuid) -> WalletMeta {
+        self.wallets.get(id).cloned().unwrap_or_default()
+    }
+
+    pub fn get_bookmark(&self, id: &Uuid) -> BookmarkMeta {
+        self.bookmarks.get(id).cloned().unwrap_or_default()
+    }
+
+    /// Sets details of an Address Book item, or removes them if they're empty
+    pub fn set_bookmark(&mut self, id: Uuid, meta: BookmarkMeta) {
+        if meta.is_empty() {
+            self.bookmarks.remove(&id);
+        } else {
+            self.bookmarks.insert(id, meta);
+        }
+    }
+}
+
+/// Lock of the metadata file for a read-modify-write, shared by all processes which use the same vault. Released
+/// when dropped
+pub struct MetaLock {
+    path: PathBuf
+}
+
+impl MetaLock {
+    fn acquire(path: PathBuf) -> Result<MetaLock, VaultError> {
+        let started = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(MetaLock { path }),
+                Err(ref e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .map(|time| time.elapsed().map(|age| age > META_LOCK_STALE).unwrap_or(false))
+                        .unwrap_or(false);
+                    if stale {
+                        let _ = fs::remove_file(&path);
+                        continue
+                    }
+                    if started.elapsed() > META_LOCK_TIMEOUT {
+                        return Err(VaultError::InvalidDataError("Metadata is locked".to_string()))
+                    }
+                    thread::sleep(Duration::from_millis(10));
+                },
+                Err(e) => return Err(VaultError::from(e))
+            }
+        }
+    }
+}
+
+impl Drop for MetaLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl WrappedVault {
+    fn meta_path(&self) -> PathBuf {
+        PathBuf::from(&self.cfg.dir).join(META_FILE)
+    }
+
+    /// Metadata of the vault, or an error if the file is corrupted. A missing file is an empty metadata
+    pub fn read_meta(&self) -> Result<VaultMeta, VaultError> {
+        match fs::read_to_string(self.meta_path()) {
+            Ok(json) => serde_json::from_str(json.as_str())
+                .map_err(|_| VaultError::InvalidDataError("Invalid metadata file".to_string())),
+            Err(_) => Ok(VaultMeta::default())
+        }
+    }
+
+    pub fn save_meta(&self, meta: &VaultMeta) -> Result<(), VaultError> {
+        let json = serde_json::to_string_pretty(meta).expect("Failed to convert to JSON");
+        // write to a temp file first to avoid a partially written metadata
+        let tmp = self.meta_path().with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, self.meta_path())?;
+        Ok(())
+    }
+
+    /// Lock for a read-modify-write of the metadata done with `read_meta` and `save_meta`, when it must be
+    /// written together with the vault storage. Otherwise use `update_meta`
+    pub fn lock_meta(&self) -> Result<MetaLock, VaultError> {
+        MetaLock::acquire(PathBuf::from(&self.cfg.dir).join(META_LOCK_FILE))
+    }
+
+    /// Changes the metadata, holding the lock so a concurrent update in another process is not lost
+    pub fn update_meta<F>(&self, f: F) -> Result<(), VaultError> where F: FnOnce(&mut VaultMeta) {
+        let _lock = self.lock_meta()?;
+        let mut meta = self.read_meta()?;
+        f(&mut meta);
+        self.save_meta(&meta)
+    }
+}
@@ -0,0 +1,227 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bitcoin::hashes::{sha256, Hash};
+use uuid::Uuid;
+
+use access::WrappedVault;
+use emerald_vault::{
+    mnemonic::{Language, Mnemonic},
+    storage::error::VaultError,
+    structs::crypto::{Aes128CtrCipher, Cipher, Encrypted, Kdf, MacType, Pbkdf2, PrfType, ScryptKdf}
+};
+
+/// Directory in the vault dir with the mnemonic phrases of the seeds, as `<seed-id>.json` files. It's not a part of
+/// the Vault storage format, and the vault itself knows only the seed bytes derived from the phrase
+const MNEMONIC_DIR: &str = "mnemonic";
+
+/// Encrypted entropy of the mnemonic phrase of a seed
+#[derive(Serialize, Deserialize)]
+struct MnemonicEntryJson {
+    version: u32,
+    language: String,
+    /// The seed was derived with a BIP-39 passphrase, which is not stored and must be provided to restore the same
+    /// seed from the phrase
+    #[serde(rename = "hasPassphrase")]
+    has_passphrase: bool,
+    entropy: EncryptedJson
+}
+
+/// Same as `Encrypted`, which has no JSON representation by itself. Binary values are hex encoded
+#[derive(Serialize, Deserialize)]
+struct EncryptedJson {
+    cipher: String,
+    #[serde(rename = "cipherText")]
+    cipher_text: String,
+    iv: String,
+    mac: String,
+    kdf: KdfJson
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum KdfJson {
+    #[serde(rename = "scrypt")]
+    Scrypt { dklen: u32, salt: String, n: u32, r: u32, p: u32 },
+    #[serde(rename = "pbkdf2")]
+    Pbkdf2 { dklen: u32, c: u32, salt: String, prf: String }
+}
+
+/// Mnemonic phrase of a seed, as stored by `WrappedVault::put_mnemonic`
+pub struct SeedMnemonic {
+    pub phrase: String,
+    pub has_passphrase: bool
+}
+
+const CIPHER_AES128_CTR: &str = "aes-128-ctr";
+const PRF_HMAC_SHA256: &str = "hmac-sha256";
+const PRF_HMAC_SHA512: &str = "hmac-sha512";
+
+fn invalid_entry() -> VaultError {
+    VaultError::InvalidDataError("mnemonic".to_string())
+}
+
+fn from_hex(value: &str) -> Result<Vec<u8>, VaultError> {
+    hex::decode(value).map_err(|_| invalid_entry())
+}
+
+impl From<&Encrypted> for EncryptedJson {
+    fn from(value: &Encrypted) -> Self {
+        let Cipher::Aes128Ctr(cipher) = &value.cipher;
+        let MacType::Web3(mac) = &cipher.mac;
+        EncryptedJson {
+            cipher: CIPHER_AES128_CTR.to_string(),
+            cipher_text: hex::encode(&cipher.encrypted),
+            iv: hex::encode(&cipher.iv),
+            mac: hex::encode(mac),
+            kdf: match &value.kdf {
+                Kdf::Scrypt(kdf) => KdfJson::Scrypt {
+                    dklen: kdf.dklen,
+                    salt: hex::encode(&kdf.salt),
+                    n: kdf.n,
+                    r: kdf.r,
+                    p: kdf.p
+                },
+                Kdf::Pbkdf2(kdf) => KdfJson::Pbkdf2 {
+                    dklen: kdf.dklen,
+                    c: kdf.c,
+                    salt: hex::encode(&kdf.salt),
+                    prf: match kdf.prf {
+                        PrfType::HmacSha256 => PRF_HMAC_SHA256.to_string(),
+                        PrfType::HmacSha512 => PRF_HMAC_SHA512.to_string()
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl EncryptedJson {
+    fn to_encrypted(&self) -> Result<Encrypted, VaultError> {
+        if self.cipher != CIPHER_AES128_CTR {
+            return Err(invalid_entry())
+        }
+        let kdf = match &self.kdf {
+            KdfJson::Scrypt { dklen, salt, n, r, p } => Kdf::Scrypt(ScryptKdf {
+                dklen: *dklen,
+                salt: from_hex(salt)?,
+                n: *n,
+                r: *r,
+                p: *p
+            }),
+            KdfJson::Pbkdf2 { dklen, c, salt, prf } => Kdf::Pbkdf2(Pbkdf2 {
+                dklen: *dklen,
+                c: *c,
+                salt: from_hex(salt)?,
+                prf: match prf.as_str() {
+                    PRF_HMAC_SHA256 => PrfType::HmacSha256,
+                    PRF_HMAC_SHA512 => PrfType::HmacSha512,
+                    _ => return Err(invalid_entry())
+                }
+            })
+        };
+        Ok(Encrypted {
+            cipher: Cipher::Aes128Ctr(Aes128CtrCipher {
+                encrypted: from_hex(self.cipher_text.as_str())?,
+                iv: from_hex(self.iv.as_str())?,
+                mac: MacType::Web3(from_hex(self.mac.as_str())?)
+            }),
+            kdf
+        })
+    }
+}
+
+/// Converts a BIP-39 phrase back to its entropy, checking the checksum bits
+fn phrase_to_entropy(phrase: &str) -> Result<Vec<u8>, VaultError> {
+    let wordlist = Language::English.wordlist();
+    let mut bits: Vec<bool> = vec![];
+    for word in phrase.split_whitespace() {
+        let index = wordlist.iter().position(|w| w == word).ok_or_else(invalid_entry)?;
+        for i in (0..11).rev() {
+            bits.push(index & (1 << i) != 0);
+        }
+    }
+    // each 32 bits of entropy has 1 bit of checksum
+    if bits.is_empty() || bits.len() % 33 != 0 {
+        return Err(invalid_entry())
+    }
+    let entropy_bits = bits.len() / 33 * 32;
+    let entropy: Vec<u8> = bits[..entropy_bits].chunks(8)
+        .map(|byte| byte.iter().fold(0u8, |acc, b| (acc << 1) | *b as u8))
+        .collect();
+    if checksum_bits(&entropy) != bits[entropy_bits..].to_vec() {
+        return Err(invalid_entry())
+    }
+    Ok(entropy)
+}
+
+fn entropy_to_phrase(entropy: &[u8]) -> Result<String, VaultError> {
+    if entropy.is_empty() || entropy.len() % 4 != 0 {
+        return Err(invalid_entry())
+    }
+    let wordlist = Language::English.wordlist();
+    let mut bits: Vec<bool> = entropy.iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte & (1 << i) != 0))
+        .collect();
+    bits.extend(checksum_bits(entropy));
+    let words: Vec<String> = bits.chunks(11)
+        .map(|word| word.iter().fold(0usize, |acc, b| (acc << 1) | *b as usize))
+        .map(|index| wordlist[index].to_string())
+        .collect();
+    Ok(words.join(" "))
+}
+
+/// First `entropy bits / 32` bits of SHA-256 of the entropy
+fn checksum_bits(entropy: &[u8]) -> Vec<bool> {
+    let hash = sha256::Hash::hash(entropy);
+    let hash = hash.into_inner();
+    (0..entropy.len() * 8 / 32)
+        .map(|i| hash[i / 8] & (0x80 >> (i % 8)) != 0)
+        .collect()
+}
+
+impl WrappedVault {
+    fn mnemonic_path(&self, seed_id: &Uuid) -> PathBuf {
+        PathBuf::from(&self.cfg.dir).join(MNEMONIC_DIR).join(format!("{}.json", seed_id))
+    }
+
+    /// Stores the entropy of the phrase of a seed, encrypted with the same password as the seed
+    pub fn put_mnemonic(&self, seed_id: &Uuid, mnemonic: &Mnemonic, has_passphrase: bool, password: &str) -> Result<(), VaultError> {
+        let entropy = phrase_to_entropy(mnemonic.sentence().as_str())?;
+        let entry = MnemonicEntryJson {
+            version: 1,
+            language: "english".to_string(),
+            has_passphrase,
+            entropy: EncryptedJson::from(&Encrypted::encrypt(entropy, password)?)
+        };
+        let json = serde_json::to_string_pretty(&entry).expect("Failed to convert to JSON");
+        let path = self.mnemonic_path(seed_id);
+        fs::create_dir_all(path.parent().unwrap())?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Mnemonic phrase of the seed, or `None` if the seed was imported without it
+    pub fn get_mnemonic(&self, seed_id: &Uuid, password: &str) -> Result<Option<SeedMnemonic>, VaultError> {
+        let json = match fs::read_to_string(self.mnemonic_path(seed_id)) {
+            Ok(json) => json,
+            Err(_) => return Ok(None)
+        };
+        let entry: MnemonicEntryJson = serde_json::from_str(json.as_str()).map_err(|_| invalid_entry())?;
+        let entropy = entry.entropy.to_encrypted()?.decrypt(password)?;
+        Ok(Some(SeedMnemonic {
+            phrase: entropy_to_phrase(&entropy)?,
+            has_passphrase: entry.has_passphrase
+        }))
+    }
+
+    pub fn remove_mnemonic(&self, seed_id: &Uuid) -> Result<(), VaultError> {
+        let path = self.mnemonic_path(seed_id);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,19 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::str::FromStr;
+
+use bitcoin::{
+    network::constants::Network,
+    secp256k1::{PublicKey, Secp256k1},
+    util::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey, ExtendedPubKey}
+};
 use neon::prelude::*;
 use uuid::Uuid;
 
-use access::{VaultConfig, WrappedVault};
+use access::{VaultConfig, WrappedVault, args_get_bool, args_get_str};
 use emerald_vault::{
     Address,
-    hdwallet::{
-        bip32::HDPath, WManager
-    },
+    hdwallet::bip32::HDPath,
     mnemonic::{
         generate_key,
         Language,
@@ -16,29 +23,82 @@ use emerald_vault::{
     storage::error::VaultError,
     structs::{
         crypto::Encrypted,
-        seed::{Seed, SeedSource}
+        seed::{LedgerSource, Seed, SeedSource},
+        wallet::{AccountId, PKType, Wallet}
     },
 };
-use json::StatusResult;
+use json::{status_code, StatusResult};
+use hardware::{address_fingerprint, parse_hd_path, storage_fingerprint, HWAddressJson, HWAppJson, HWKey, HWKeyError};
+use meta::{now_millis, SeedMeta};
 use emerald_vault::util::optional::none_if_empty;
+use serde::{Deserialize, Deserializer};
+use sha3::{Digest, Keccak256};
 
 struct HDPathAddress {
     address: Address,
     hd_path: String
 }
 
+#[derive(Serialize, Clone)]
+pub struct HDPathAddressJson {
+    pub address: String,
+    #[serde(rename = "hdPath")]
+    pub hd_path: String
+}
+
+impl From<HDPathAddress> for HDPathAddressJson {
+    fn from(value: HDPathAddress) -> Self {
+        HDPathAddressJson {
+            address: value.address.to_string(),
+            hd_path: value.hd_path
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SeedJson {
     pub id: String,
+    #[serde(rename = "type")]
     pub seed_type: SeedType,
-    pub is_available: bool
+    #[serde(rename = "available")]
+    pub is_available: bool,
+    pub label: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<u64>,
+    pub accounts: Vec<String>
+}
+
+#[derive(Serialize, Clone)]
+pub struct SeedRemoveJson {
+    pub removed: bool,
+    /// accounts which use the seed, i.e. which are (or would become) unusable after removal
+    pub accounts: Vec<String>
+}
+
+#[derive(Serialize, Clone)]
+pub enum SeedExportType {
+    #[serde(rename = "mnemonic")]
+    Mnemonic,
+    #[serde(rename = "raw")]
+    Raw
+}
+
+#[derive(Serialize, Clone)]
+pub struct SeedExportJson {
+    #[serde(rename = "type")]
+    pub export_type: SeedExportType,
+    pub value: String,
+    /// For a mnemonic, whether the seed was created with a BIP-39 passphrase, which is required to restore
+    /// the same seed from the phrase
+    pub passphrase: Option<bool>
 }
 
+/// Type of a stored seed, same values as `SeedType` in types.ts
 #[derive(Serialize, Deserialize, Clone)]
 pub enum SeedType {
     #[serde(rename = "ledger")]
     Ledger,
-    #[serde(rename = "bytes")]
+    #[serde(rename = "raw")]
     Bytes
 }
 
@@ -53,7 +113,29 @@ pub struct SeedDefinitionJson {
 #[serde(tag = "type", content = "value")]
 pub enum SeedDefinitionType {
     #[serde(rename = "mnemonic")]
-    Mnemonic(MnemonicSeedJson)
+    Mnemonic(MnemonicSeedJson),
+    #[serde(rename = "seed-ref")]
+    Reference(String),
+    #[serde(rename = "ledger", deserialize_with = "deserialize_ledger_seed")]
+    Ledger(LedgerSeedJson)
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct LedgerSeedJson {
+    /// Device to use, the first connected one if not set
+    pub device: Option<String>
+}
+
+/// Reads the details of a Ledger seed. A value other than an object, like the `"any"` passed by older code, means
+/// any connected device
+fn deserialize_ledger_seed<'de, D>(deserializer: D) -> Result<LedgerSeedJson, D::Error>
+    where D: Deserializer<'de> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    if value.is_object() {
+        serde_json::from_value(value).map_err(serde::de::Error::custom)
+    } else {
+        Ok(LedgerSeedJson::default())
+    }
 }
 
 #[derive(Deserialize, Clone)]
@@ -72,10 +154,66 @@ impl From<Seed> for SeedJson {
             },
             is_available: match value.source {
                 SeedSource::Bytes(_) => true,
-                SeedSource::Ledger(_) => false, //TODO
+                // availability of a Ledger depends on the connected device, see `with_ledger`
+                SeedSource::Ledger(_) => false,
+            },
+            label: None,
+            created_at: None,
+            accounts: vec![]
+        }
+    }
+}
+
+impl SeedJson {
+    fn with_meta(self, meta: SeedMeta) -> Self {
+        SeedJson {
+            label: meta.label,
+            created_at: meta.created_at,
+            ..self
+        }
+    }
+
+    fn with_accounts(self, accounts: Vec<String>) -> Self {
+        SeedJson {
+            accounts,
+            ..self
+        }
+    }
+
+    /// Marks a Ledger seed as available if one of the connected devices has the same fingerprint. Seeds created
+    /// before fingerprints were stored match any connected Ledger
+    fn with_ledger(self, fingerprint: Option<String>, connected: &[String]) -> Self {
+        match self.seed_type {
+            SeedType::Ledger => {
+                let is_available = match fingerprint {
+                    Some(expected) => connected.contains(&expected),
+                    None => !connected.is_empty()
+                };
+                SeedJson {
+                    is_available,
+                    ..self
+                }
+            },
+            SeedType::Bytes => self
+        }
+    }
+}
+
+/// Ids of all accounts which use the seed
+fn find_seed_accounts(wallets: &Vec<Wallet>, seed_id: &Uuid) -> Vec<String> {
+    let mut result = vec![];
+    for wallet in wallets {
+        for account in &wallet.accounts {
+            let is_using = match &account.key {
+                PKType::SeedHd(seed) => seed.seed_id == *seed_id,
+                PKType::PrivateKeyRef(_) => false
+            };
+            if is_using {
+                result.push(AccountId::from(wallet, account).to_string());
             }
         }
     }
+    result
 }
 
 impl SeedDefinitionJson {
@@ -90,58 +228,163 @@ impl SeedDefinitionJson {
     }
 }
 
-fn list_ledger_address(hd_path_all: Vec<String>) -> Vec<HDPathAddress> {
+fn list_hw_address(hd_path_all: Vec<String>, key: &dyn HWKey) -> Result<Vec<HDPathAddress>, HWKeyError> {
     let mut result = vec![];
-
-    let id = HDPath::try_from("m/44'/60'/0'/0'/0").expect("Failed to create address");
-    let mut wallet_manager = WManager::new(Some(id.to_bytes())).expect("Can't create HID endpoint");
-    if !wallet_manager.open().is_ok() {
-        return result;
-    }
-    wallet_manager.update(None).expect("Devices list not loaded");
-
-    let fd = &wallet_manager.devices()[0].1;
-
     for item in hd_path_all {
-        let hd_path = HDPath::try_from(item.as_str()).expect("Failed to create address");
-        let address = wallet_manager.get_address(fd.as_str(), Some(hd_path.to_bytes()))
-            .expect("Filed to get address from Ledger");
+        let address = key.get_address(&parse_hd_path(item.as_str())?)?;
         result.push(HDPathAddress {address, hd_path: item})
     }
-
-    result
+    Ok(result)
 }
 
-fn list_mnemonic_address(hd_path_all: Vec<String>, mnemonic: Mnemonic, password: Option<String>) -> Vec<HDPathAddress> {
-    let mut result = vec![];
+fn list_mnemonic_address(hd_path_all: Vec<String>, mnemonic: Mnemonic, password: Option<String>) -> Result<Vec<HDPathAddress>, VaultError> {
     let seed = match password {
         Some(p) => mnemonic.seed(Some(p.as_str())),
         None => mnemonic.seed(None)
     };
+    list_seed_address(hd_path_all, &seed)
+}
+
+fn hw_status<T>(result: Result<T, HWKeyError>) -> StatusResult<T> {
+    match result {
+        Ok(t) => StatusResult::Ok(t),
+        Err(e) => StatusResult::Error(e.status_code(), format!("Hardware Wallet error: {:?}", e))
+    }
+}
+
+fn list_seed_address(hd_path_all: Vec<String>, seed: &[u8]) -> Result<Vec<HDPathAddress>, VaultError> {
+    let mut result = vec![];
     for item in hd_path_all {
         let hd_path = HDPath::try_from(item.as_str())
-            .expect("Failed to create address");
-        let pk = generate_key(&hd_path, &seed)
-            .expect("Unable to generate private key");
+            .map_err(|_| VaultError::InvalidDataError("hd_path".to_string()))?;
+        let pk = generate_key(&hd_path, seed)
+            .map_err(|_| VaultError::InvalidDataError("seed".to_string()))?;
         let address = pk.to_address();
         result.push(HDPathAddress {address, hd_path: item})
     }
-    result
+    Ok(result)
+}
+
+/// Max number of addresses derived by a single call, i.e. `count` of a range
+pub const MAX_ADDRESS_COUNT: u32 = 1000;
+
+/// Range of address indexes `start..start + count`, rejected if it overflows or is larger than `MAX_ADDRESS_COUNT`
+pub fn address_range(start: u32, count: u32) -> Result<Range<u32>, VaultError> {
+    if count > MAX_ADDRESS_COUNT {
+        return Err(VaultError::InvalidDataError("count".to_string()))
+    }
+    let end = start.checked_add(count)
+        .ok_or_else(|| VaultError::InvalidDataError("start".to_string()))?;
+    Ok(start..end)
+}
+
+/// Puts the index into a path template. A template marks the index position with `*`, ex. `m/44'/60'/*'/0/0`
+/// for Ledger Live or `m/44'/60'/0'/*` for legacy MEW paths; a template without `*` gets the index appended as
+/// the last element
+fn expand_hd_path(template: &str, index: u32) -> String {
+    if template.contains('*') {
+        template.replace('*', index.to_string().as_str())
+    } else {
+        format!("{}/{}", template.trim_end_matches('/'), index)
+    }
+}
+
+/// Derives BIP-32 extended public key at the specified path. Uses the BIP-32 implementation of `bitcoin` crate,
+/// because `generate_key` returns only the private key and not the chain code an xpub needs. The result must give
+/// the same child keys as `generate_key` for the paths below it, which the "Same addresses as the seed" test checks
+fn derive_xpub(seed: &[u8], hd_path: &str) -> Result<ExtendedPubKey, VaultError> {
+    let secp = Secp256k1::new();
+    let path = DerivationPath::from_str(hd_path)
+        .map_err(|_| VaultError::InvalidDataError("hd_path".to_string()))?;
+    let key = ExtendedPrivKey::new_master(Network::Bitcoin, seed)
+        .and_then(|k| k.derive_priv(&secp, &path))
+        .map_err(|_| VaultError::InvalidDataError("seed".to_string()))?;
+    Ok(ExtendedPubKey::from_private(&secp, &key))
+}
+
+pub fn public_key_to_address(key: &PublicKey) -> Address {
+    let hash = Keccak256::digest(&key.serialize_uncompressed()[1..]);
+    Address::from_str(format!("0x{}", hex::encode(&hash[12..])).as_str())
+        .expect("Invalid address")
+}
+
+/// Lists addresses on the external chain (`<xpub>/0/i`) of an account-level xpub
+pub fn list_xpub_addresses(xpub: &str, start: u32, count: u32) -> Result<Vec<Address>, VaultError> {
+    let secp = Secp256k1::new();
+    let xpub = ExtendedPubKey::from_str(xpub)
+        .map_err(|_| VaultError::InvalidDataError("xpub".to_string()))?;
+    let mut result = vec![];
+    for index in address_range(start, count)? {
+        let path = ChildNumber::from_normal_idx(0)
+            .and_then(|change| ChildNumber::from_normal_idx(index).map(|i| [change, i]))
+            .map_err(|_| VaultError::InvalidDataError("index".to_string()))?;
+        let key = xpub.derive_pub(&secp, &path)
+            .map_err(|_| VaultError::InvalidDataError("xpub".to_string()))?;
+        result.push(public_key_to_address(&key.public_key.key));
+    }
+    Ok(result)
 }
 
 pub fn is_connected(mut cx: FunctionContext) -> JsResult<JsBoolean> {
-    let id = HDPath::try_from("m/44'/60'/0'/0'/0").expect("Failed to create address");
-    let wallet_manager = WManager::new(Some(id.to_bytes())).expect("Can't create HID endpoint");
-    let is_connected = wallet_manager.open().is_ok();
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+    let device = args_get_str(&mut cx, 1);
+    let is_connected = vault.get_hw_key(device)
+        .map(|key| key.is_connected())
+        .unwrap_or(false);
 
     let result = cx.boolean(is_connected);
     Ok(result)
 }
 
+/// Same as `is_connected` but with details about the app opened on the device
+pub fn get_app(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+    let device = args_get_str(&mut cx, 1);
+    let result = match vault.get_hw_key(device) {
+        Ok(key) => key.get_app_state(),
+        Err(_) => HWAppJson::disconnected()
+    };
+
+    let status = StatusResult::Ok(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
+/// Shows the address on the device screen, to let the user verify it before using
+pub fn confirm_address(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+    let hd_path = cx.argument::<JsString>(1).expect("HD Path is not provided").value();
+    let device = args_get_str(&mut cx, 2);
+
+    let result = parse_hd_path(hd_path.as_str())
+        .and_then(|path| vault.get_hw_key(device).and_then(|key| key.confirm_address(&path)));
+    let result = match result {
+        Ok(address) => StatusResult::Ok(HWAddressJson {
+            address: Some(address.to_string()),
+            confirmed: true
+        }),
+        Err(HWKeyError::Rejected) => StatusResult::Ok(HWAddressJson {
+            address: None,
+            confirmed: false
+        }),
+        Err(e) => StatusResult::Error(e.status_code(), format!("Hardware Wallet error: {:?}", e))
+    };
+
+    let status = result.as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
 pub fn list_addresses(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let json = cx.argument::<JsString>(0).expect("Input JSON is not provided").value();
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
 
-    let hd_path_all = cx.argument::<JsArray>(2)
+    let json = cx.argument::<JsString>(1).expect("Input JSON is not provided").value();
+
+    let hd_path_all = cx.argument::<JsArray>(3)
         .expect("List of HD Path is not provided")
         .to_vec(&mut cx)
         .expect("Failed to convert to Rust vector")
@@ -149,33 +392,25 @@ pub fn list_addresses(mut cx: FunctionContext) -> JsResult<JsObject> {
         .map(|item| {
             item.downcast::<JsString>().expect("Expected string element in array").value()
         }).collect();
-
-    let mut js_object = JsObject::new(&mut cx);
+    let device = args_get_str(&mut cx, 4);
 
     let parsed: SeedDefinitionJson = serde_json::from_str(json.as_str()).expect("Invalid JSON");
+    let parsed = parsed.clean();
 
-    let addresses: Vec<HDPathAddress> = match parsed.seed_type {
-//            SeedDefinitionType::Ledger => {
-//                list_ledger_address(hd_path_all)
-//            },
-        SeedDefinitionType::Mnemonic(m) => {
-            let mnemonic = Mnemonic::try_from(Language::English, m.value.as_str())
-                .expect("Failed to parse mnemonic phrase");
-            list_mnemonic_address(hd_path_all, mnemonic, m.password)
-        }
-    };
-
-    for address in addresses {
-        let address_js = cx.string(address.address.to_string());
-        js_object.set(&mut cx, address.hd_path.as_str(), address_js)
-            .expect("Failed to setup result");
-    }
+    let result = vault.list_seed_addresses(parsed, hd_path_all, device)
+        .map(|addresses| {
+            addresses.into_iter()
+                .map(|a| (a.hd_path, a.address.to_string()))
+                .collect::<HashMap<String, String>>()
+        });
 
-    Ok(js_object)
+    let status = result.as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
 }
 
 pub fn add(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let json = cx.argument::<JsString>(1).expect("Input JSON is not provided").value();
@@ -190,16 +425,98 @@ pub fn add(mut cx: FunctionContext) -> JsResult<JsObject> {
 }
 
 pub fn list(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
-    let seeds = vault.list_seeds().expect("List not loaded");
+    let result = vault.describe_seeds();
+
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
 
-    let result: Vec<SeedJson> = seeds.iter().map(|s| SeedJson::from(s.clone())).collect();
+pub fn update_label(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let seed_id = cx.argument::<JsString>(1).expect("Seed ID is not provided").value();
+    let seed_id = Uuid::parse_str(seed_id.as_str()).expect("Invalid Seed ID");
+    let label = args_get_str(&mut cx, 2);
+
+    let result = vault.set_seed_label(seed_id, label).is_ok();
     let status = StatusResult::Ok(result).as_json();
     let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
     Ok(js_value.downcast().unwrap())
 }
 
+pub fn remove(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let seed_id = cx.argument::<JsString>(1).expect("Seed ID is not provided").value();
+    let seed_id = Uuid::parse_str(seed_id.as_str()).expect("Invalid Seed ID");
+    let password = args_get_str(&mut cx, 2);
+    let force = args_get_bool(&mut cx, 3).unwrap_or(false);
+
+    let result = vault.remove_seed(seed_id, password, force);
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn export(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let seed_id = cx.argument::<JsString>(1).expect("Seed ID is not provided").value();
+    let seed_id = Uuid::parse_str(seed_id.as_str()).expect("Invalid Seed ID");
+    let password = cx.argument::<JsString>(2).expect("Password is not provided").value();
+
+    let result = vault.export_seed(seed_id, password);
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn get_xpub(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let seed_id = cx.argument::<JsString>(1).expect("Seed ID is not provided").value();
+    let seed_id = Uuid::parse_str(seed_id.as_str()).expect("Invalid Seed ID");
+    let password = cx.argument::<JsString>(2).expect("Password is not provided").value();
+    let hd_path = cx.argument::<JsString>(3).expect("HD Path is not provided").value();
+
+    let result = vault.get_xpub(seed_id, password, hd_path)
+        .map(|xpub| xpub.to_string());
+
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn scan_addresses(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let seed_id = cx.argument::<JsString>(1).expect("Seed ID is not provided").value();
+    let seed_id = Uuid::parse_str(seed_id.as_str()).expect("Invalid Seed ID");
+    let password = cx.argument::<JsString>(2).expect("Password is not provided").value();
+    let base_path = cx.argument::<JsString>(3).expect("Base HD Path is not provided").value();
+    let start = cx.argument::<JsNumber>(4).expect("Start index is not provided").value() as u32;
+    let count = cx.argument::<JsNumber>(5).expect("Count is not provided").value() as u32;
+
+    let result = vault.scan_seed_addresses(seed_id, password, base_path, start, count)
+        .map(|addresses| {
+            addresses.into_iter()
+                .map(HDPathAddressJson::from)
+                .collect::<Vec<HDPathAddressJson>>()
+        });
+
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
+    Ok(js_value.downcast().unwrap())
+}
+
 pub fn generate_mnemonic(mut cx: FunctionContext) -> JsResult<JsObject> {
     let size = cx.argument::<JsNumber>(0)
         .expect("Mnemonic size is not provided").value() as usize;
@@ -214,32 +531,208 @@ pub fn generate_mnemonic(mut cx: FunctionContext) -> JsResult<JsObject> {
 }
 
 impl WrappedVault {
+    /// Addresses of a seed at the HD Paths. A seed in the vault requires its password, a Hardware Wallet must
+    /// be connected
+    fn list_seed_addresses(&self, seed: SeedDefinitionJson, hd_path_all: Vec<String>, device: Option<String>) -> StatusResult<Vec<HDPathAddress>> {
+        match seed.seed_type {
+            SeedDefinitionType::Ledger(_) => {
+                hw_status(self.get_hw_key(device).and_then(|key| list_hw_address(hd_path_all, key.as_ref())))
+            },
+            SeedDefinitionType::Mnemonic(m) => {
+                let result = Mnemonic::try_from(Language::English, m.value.as_str())
+                    .map_err(|_| VaultError::InvalidDataError("mnemonic".to_string()))
+                    .and_then(|mnemonic| list_mnemonic_address(hd_path_all, mnemonic, m.password));
+                StatusResult::from(result)
+            },
+            SeedDefinitionType::Reference(id) => {
+                let seed_id = match Uuid::parse_str(id.as_str()) {
+                    Ok(id) => id,
+                    Err(_) => return StatusResult::Error(status_code::VAULT_ERROR, "Invalid Seed ID".to_string())
+                };
+                let is_ledger = match self.is_ledger_seed(seed_id) {
+                    Ok(is_ledger) => is_ledger,
+                    Err(_) => return StatusResult::Error(status_code::VAULT_ERROR, "Seed is not available".to_string())
+                };
+                if is_ledger {
+                    let key = match device {
+                        Some(_) => self.get_hw_key(device),
+                        None => self.get_hw_key_for_seed(&seed_id)
+                    };
+                    hw_status(key.and_then(|key| list_hw_address(hd_path_all, key.as_ref())))
+                } else {
+                    let password = match seed.password {
+                        Some(password) => password,
+                        None => return StatusResult::Error(status_code::VAULT_ERROR, "Password is required".to_string())
+                    };
+                    let result = self.get_seed_bytes(seed_id, password)
+                        .and_then(|seed| list_seed_address(hd_path_all, &seed));
+                    StatusResult::from(result)
+                }
+            }
+        }
+    }
+
+    /// All seeds with their details and accounts, and whether a Ledger seed is on one of the connected devices
+    fn describe_seeds(&self) -> Result<Vec<SeedJson>, VaultError> {
+        let seeds = self.list_seeds()?;
+        let meta = self.read_meta()?;
+        let wallets = self.load_wallets();
+
+        let has_ledger = seeds.iter().any(|s| match s.source {
+            SeedSource::Ledger(_) => true,
+            SeedSource::Bytes(_) => false
+        });
+        // ask the devices only if there is a Ledger seed to check
+        let connected = if has_ledger {
+            self.get_connected_fingerprints()
+        } else {
+            vec![]
+        };
+
+        let result = seeds.iter()
+            .map(|s| {
+                let seed_meta = meta.get_seed(&s.id);
+                SeedJson::from(s.clone())
+                    .with_ledger(seed_meta.fingerprint.clone(), &connected)
+                    .with_meta(seed_meta)
+                    .with_accounts(find_seed_accounts(&wallets, &s.id))
+            })
+            .collect();
+        Ok(result)
+    }
+
+    pub fn get_seed(&self, seed_id: &Uuid) -> Result<Seed, VaultError> {
+        let storage = &self.cfg.get_storage();
+        storage.seeds().get(seed_id)
+    }
+
     pub fn list_seeds(&self) -> Result<Vec<Seed>, VaultError> {
         let storage = &self.cfg.get_storage();
         storage.seeds().list_entries()
     }
 
+    /// Returns the mnemonic phrase if it was stored with the seed, otherwise the raw seed bytes
+    fn export_seed(&self, seed_id: Uuid, password: String) -> Result<SeedExportJson, VaultError> {
+        let seed = self.get_seed_bytes(seed_id, password.clone())?;
+        match self.get_mnemonic(&seed_id, password.as_str())? {
+            Some(mnemonic) => Ok(SeedExportJson {
+                export_type: SeedExportType::Mnemonic,
+                value: mnemonic.phrase,
+                passphrase: Some(mnemonic.has_passphrase)
+            }),
+            None => Ok(SeedExportJson {
+                export_type: SeedExportType::Raw,
+                value: hex::encode(seed),
+                passphrase: None
+            })
+        }
+    }
+
+    pub fn get_seed_bytes(&self, seed_id: Uuid, password: String) -> Result<Vec<u8>, VaultError> {
+        let seed = self.get_seed(&seed_id)?;
+        match seed.source {
+            SeedSource::Bytes(bytes) => Ok(bytes.decrypt(password.as_str())?),
+            SeedSource::Ledger(_) => Err(VaultError::UnsupportedDataError("Ledger seed has no raw bytes".to_string()))
+        }
+    }
+
+    pub fn is_ledger_seed(&self, seed_id: Uuid) -> Result<bool, VaultError> {
+        let seed = self.get_seed(&seed_id)?;
+        match seed.source {
+            SeedSource::Ledger(_) => Ok(true),
+            SeedSource::Bytes(_) => Ok(false)
+        }
+    }
+
+    pub fn get_xpub(&self, seed_id: Uuid, password: String, hd_path: String) -> Result<ExtendedPubKey, VaultError> {
+        let seed = self.get_seed_bytes(seed_id, password)?;
+        derive_xpub(&seed, hd_path.as_str())
+    }
+
+    fn scan_seed_addresses(&self, seed_id: Uuid, password: String, base_path: String, start: u32, count: u32) -> Result<Vec<HDPathAddress>, VaultError> {
+        let seed = self.get_seed_bytes(seed_id, password)?;
+        let hd_path_all = address_range(start, count)?
+            .map(|index| expand_hd_path(base_path.as_str(), index))
+            .collect();
+        list_seed_address(hd_path_all, &seed)
+    }
+
     pub fn add_seed(&self, seed: SeedDefinitionJson) -> Result<Uuid, VaultError> {
         let storage = &self.cfg.get_storage();
+        let mut mnemonic = None;
+        let mut fingerprint = None;
         let seed_source = match seed.seed_type {
-//            SeedDefinitionType::Ledger => {
-//                SeedSource::Ledger(LedgerSource {
-//                    fingerprints: vec![]
-//                })
-//            },
+            SeedDefinitionType::Ledger(value) => {
+                let address = self.get_hw_key(value.device)?.fingerprint_address()?;
+                // metadata keeps a copy of the fingerprint, to find the device without reading the seed
+                fingerprint = Some(address_fingerprint(&address));
+                SeedSource::Ledger(LedgerSource {
+                    fingerprints: vec![storage_fingerprint(&address)?]
+                })
+            },
             SeedDefinitionType::Mnemonic(value) => {
                 if seed.password.is_none() {
                     return Err(VaultError::PasswordRequired)
                 }
-                let mnemonic = Mnemonic::try_from(Language::English, value.value.as_str())
+                let phrase = Mnemonic::try_from(Language::English, value.value.as_str())
                     .map_err(|e| VaultError::InvalidDataError("mnemonic".to_string()))?;
 //                let mnemonic_password = value.password.as_deref();
                 let mnemonic_password = value.password.as_ref().map(|x| &**x);
-                let raw = mnemonic.seed(mnemonic_password);
-                SeedSource::Bytes(Encrypted::encrypt(raw, seed.password.unwrap().as_str())?)
+                let raw = phrase.seed(mnemonic_password);
+                let password = seed.password.clone().unwrap();
+                // an empty passphrase gives the same seed as no passphrase
+                mnemonic = Some((phrase, mnemonic_password.map_or(false, |p| !p.is_empty())));
+                SeedSource::Bytes(Encrypted::encrypt(raw, password.as_str())?)
+            },
+            SeedDefinitionType::Reference(_) => {
+                return Err(VaultError::InvalidDataError("Seed is already stored".to_string()))
             }
         };
         let id = storage.seeds().add(Seed { id: Uuid::new_v4(), source: seed_source })?;
+        if let Some((phrase, has_passphrase)) = mnemonic {
+            self.put_mnemonic(&id, &phrase, has_passphrase, seed.password.unwrap().as_str())?;
+        }
+        self.update_meta(|meta| {
+            meta.seeds.insert(id, SeedMeta {
+                label: None,
+                created_at: Some(now_millis()),
+                fingerprint
+            });
+        })?;
         Ok(id)
     }
+
+    /// Removes the seed unless it's used by an account, or `force` is set. Seeds with key material
+    /// are removed only with a correct password
+    fn remove_seed(&self, seed_id: Uuid, password: Option<String>, force: bool) -> Result<SeedRemoveJson, VaultError> {
+        let storage = &self.cfg.get_storage();
+        let seed = self.get_seed(&seed_id)?;
+        if let SeedSource::Bytes(bytes) = seed.source {
+            let password = password.ok_or(VaultError::PasswordRequired)?;
+            bytes.decrypt(password.as_str())?;
+        }
+
+        let accounts = find_seed_accounts(&self.load_wallets(), &seed_id);
+        if !accounts.is_empty() && !force {
+            return Ok(SeedRemoveJson { removed: false, accounts })
+        }
+
+        let removed = storage.seeds().remove(&seed_id)?;
+        if removed {
+            self.remove_mnemonic(&seed_id)?;
+            self.update_meta(|meta| {
+                meta.seeds.remove(&seed_id);
+            })?;
+        }
+        Ok(SeedRemoveJson { removed, accounts })
+    }
+
+    fn set_seed_label(&self, seed_id: Uuid, label: Option<String>) -> Result<(), VaultError> {
+        // make sure the seed exists
+        self.get_seed(&seed_id)?;
+        let label = label.and_then(|s| none_if_empty(s.as_str()));
+        self.update_meta(|meta| {
+            meta.seeds.entry(seed_id).or_insert_with(SeedMeta::default).label = label;
+        })
+    }
 }
\ No newline at end of file
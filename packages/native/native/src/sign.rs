@@ -5,17 +5,22 @@ use hex::FromHex;
 use neon::prelude::{FunctionContext, JsNumber, JsObject, JsResult, JsString};
 use uuid::Uuid;
 
-use access::{VaultConfig, WrappedVault};
+use access::{VaultConfig, WrappedVault, args_get_str};
 use emerald_vault::{
     Address,
     align_bytes,
+    core::chains::EthereumChainId,
+    hdwallet::bip32::HDPath,
+    structs::wallet::{PKType, WalletAccount},
     to_arr,
     to_even_str,
     to_u64,
     Transaction,
     trim_hex
 };
-use json::{JsonError, StatusResult};
+use hardware::decode_hd_path;
+use json::{JsonError, StatusResult, status_code};
+use wallets::is_watch_only;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct UnsignedTx {
@@ -62,12 +67,55 @@ impl TryInto<Transaction> for UnsignedTx {
 
 impl WrappedVault {
 
+    /// Seed and HD Path of the account if it's a key on a Hardware Wallet
+    fn get_ledger_path(&self, account: &WalletAccount) -> Option<(Uuid, HDPath)> {
+        match &account.key {
+            PKType::SeedHd(seed) => {
+                if self.is_ledger_seed(seed.seed_id).unwrap_or(false) {
+                    Some((seed.seed_id, seed.hd_path.clone()))
+                } else {
+                    None
+                }
+            },
+            PKType::PrivateKeyRef(_) => None
+        }
+    }
+
+    fn sign_with_ledger(&self, account: &WalletAccount, seed_id: Uuid, hd_path: HDPath, tx: Transaction) -> StatusResult<Vec<u8>> {
+        let key = match self.get_hw_key_for_seed(&seed_id) {
+            Ok(key) => key,
+            Err(e) => return StatusResult::Error(e.status_code(), format!("Hardware Wallet error: {:?}", e))
+        };
+        let chain_id = EthereumChainId::from(account.blockchain).as_chainid();
+        let hd_path = match decode_hd_path(&hd_path.to_bytes()) {
+            Some((path, _)) => path,
+            None => return StatusResult::Error(status_code::HW_ERROR, "Invalid HD Path".to_string())
+        };
+        if let Some(expected) = account.address {
+            // make sure it's the same seed, otherwise the transaction would be signed by another key
+            match key.get_address(&hd_path) {
+                Ok(address) => if address != expected {
+                    return StatusResult::Error(status_code::HW_ERROR, "Connected Hardware Wallet has a different seed".to_string())
+                },
+                Err(e) => return StatusResult::Error(e.status_code(), format!("Hardware Wallet error: {:?}", e))
+            }
+        }
+        match key.sign_transaction(&hd_path, &tx, chain_id) {
+            Ok(signature) => StatusResult::Ok(tx.raw_from_sig(chain_id, &signature)),
+            Err(e) => StatusResult::Error(e.status_code(), format!("Hardware Wallet error: {:?}", e))
+        }
+    }
+
     fn sign_tx(&self, wallet_id: Uuid, account_id: usize,
-               unsigned_tx: UnsignedTx, password: String, ) -> Vec<u8> {
+               unsigned_tx: UnsignedTx, password: Option<String>) -> StatusResult<Vec<u8>> {
         let storage = &self.cfg.get_storage();
         let wallet = storage.wallets().get(&wallet_id).expect("Wallet doesn't exist");
         let account = wallet.get_account(account_id).expect("Account not found");
 
+        if is_watch_only(&account) {
+            return StatusResult::Error(status_code::WATCH_ONLY, "Watch-only account cannot sign transactions".to_string())
+        }
+
         let from_address = Address::from_str(unsigned_tx.from.as_str()).expect("Invalid from address");
 
         if account.address.is_some() && account.address.unwrap() != from_address {
@@ -76,13 +124,20 @@ impl WrappedVault {
 
         let tx: Transaction = unsigned_tx.try_into().expect("Invalid sign JSON");
 
-        account.sign_tx(tx, Some(password), &storage)
-            .expect("Failed to sign")
+        if let Some((seed_id, hd_path)) = self.get_ledger_path(&account) {
+            return self.sign_with_ledger(&account, seed_id, hd_path, tx)
+        }
+
+        let password = match password {
+            Some(password) => password,
+            None => return StatusResult::Error(status_code::VAULT_ERROR, "Password is required".to_string())
+        };
+        StatusResult::from(account.sign_tx(tx, Some(password), &storage))
     }
 }
 
 pub fn sign_tx(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let wallet_id = cx.argument::<JsString>(1).expect("wallet_id not provided").value();
@@ -93,11 +148,12 @@ pub fn sign_tx(mut cx: FunctionContext) -> JsResult<JsObject> {
     let unsigned_tx = cx.argument::<JsString>(3).expect("Transaction JSON not provided").value();
     let unsigned_tx = serde_json::from_str::<UnsignedTx>(unsigned_tx.as_str())
         .expect("Invalid transaction JSON");
-    let password = cx.argument::<JsString>(4).expect("Password not provided").value();
+    // not used by a Hardware Wallet account
+    let password = args_get_str(&mut cx, 4);
 
-    let result = vault.sign_tx(wallet_id, account_id, unsigned_tx, password);
-    let result = hex::encode(result);
-    let status = StatusResult::Ok(result).as_json();
+    let result = vault.sign_tx(wallet_id, account_id, unsigned_tx, password)
+        .map(hex::encode);
+    let status = result.as_json();
     let js_value = neon_serde::to_value(&mut cx, &status).expect("Invalid Value");
     Ok(js_value.downcast().unwrap())
 }
\ No newline at end of file
@@ -0,0 +1,263 @@
+use std::ffi::CString;
+
+use hidapi::{HidApi, HidDevice, HidDeviceInfo};
+
+use hardware::{HWKeyError, Vendor};
+
+/// Status Word of a successfully executed command
+pub const SW_OK: u16 = 0x9000;
+pub const SW_USER_REJECTED: u16 = 0x6985;
+pub const SW_LOCKED: u16 = 0x5515;
+/// Returned by a locked device with an older firmware
+pub const SW_SECURITY_STATUS: u16 = 0x6982;
+pub const SW_INS_NOT_SUPPORTED: u16 = 0x6d00;
+pub const SW_CLA_NOT_SUPPORTED: u16 = 0x6e00;
+/// Returned by the dashboard when no app is open
+pub const SW_APP_NOT_OPEN: u16 = 0x6511;
+
+const LEDGER_VENDOR_ID: u16 = 0x2c97;
+/// Vendor and product of the Nano S with an old firmware
+const LEDGER_LEGACY_VENDOR_ID: u16 = 0x2581;
+const LEDGER_LEGACY_PRODUCT_ID: u16 = 0x3b7c;
+const LEDGER_USAGE_PAGE: u16 = 0xffa0;
+
+const TREZOR_VENDOR_ID: u16 = 0x534c;
+const TREZOR_PRODUCT_ID: u16 = 0x0001;
+/// Trezor Model T, and Trezor One with a newer bootloader
+const TREZOR_V2_VENDOR_ID: u16 = 0x1209;
+const TREZOR_V2_PRODUCT_ID: u16 = 0x53c1;
+/// Interface with the wallet messages, others are used for debug and U2F
+const TREZOR_INTERFACE: i32 = 0;
+
+const HID_CHANNEL: u16 = 0x0101;
+const HID_TAG_APDU: u8 = 0x05;
+const HID_PACKET_SIZE: usize = 64;
+const HID_READ_TIMEOUT_MS: i32 = 10_000;
+/// Confirmation on the device may take much longer than a regular response
+const HID_CONFIRM_TIMEOUT_MS: i32 = 120_000;
+/// Size of the Trezor message header: magic `##`, type (2 bytes), payload length (4 bytes)
+pub const TREZOR_HEADER_SIZE: usize = 8;
+
+/// Error for a non-success Status Word
+fn status_error(sw: u16) -> HWKeyError {
+    match sw {
+        SW_LOCKED | SW_SECURITY_STATUS => HWKeyError::Locked,
+        SW_INS_NOT_SUPPORTED | SW_CLA_NOT_SUPPORTED | SW_APP_NOT_OPEN => HWKeyError::WrongApp,
+        SW_USER_REJECTED => HWKeyError::Rejected,
+        _ => HWKeyError::DeviceError(sw)
+    }
+}
+
+/// APDU command sent to a Hardware Wallet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Apdu {
+    pub cla: u8,
+    pub ins: u8,
+    pub p1: u8,
+    pub p2: u8,
+    pub data: Vec<u8>
+}
+
+impl Apdu {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = vec![self.cla, self.ins, self.p1, self.p2, self.data.len() as u8];
+        result.extend_from_slice(&self.data);
+        result
+    }
+
+    /// Parses a command received by the emulator
+    #[cfg(feature = "hw-emulator")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Apdu, HWKeyError> {
+        if bytes.len() < 5 || bytes.len() != 5 + bytes[4] as usize {
+            return Err(HWKeyError::CommunicationError("Invalid APDU".to_string()))
+        }
+        Ok(Apdu {
+            cla: bytes[0],
+            ins: bytes[1],
+            p1: bytes[2],
+            p2: bytes[3],
+            data: bytes[5..].to_vec()
+        })
+    }
+}
+
+/// Connection to a Hardware Wallet, on the level of the commands of its protocol, i.e. APDU for Ledger and
+/// protocol messages for Trezor. Implemented by the USB HID connection and by the emulators used in tests
+pub trait HwTransport {
+    /// Identifies the device between the connected ones
+    fn id(&self) -> String;
+
+    fn is_connected(&self) -> bool;
+
+    /// Sends a command and returns the response. For Ledger it's an APDU command and the response has the
+    /// Status Word as the last two bytes, for Trezor it's a message with its header
+    fn send(&self, data: &[u8]) -> Result<Vec<u8>, HWKeyError>;
+
+    /// Sends APDU command and returns the response data if the device executed it successfully. Ledger only
+    fn exchange(&self, apdu: &Apdu) -> Result<Vec<u8>, HWKeyError> {
+        let mut response = self.send(&apdu.to_bytes())?;
+        if response.len() < 2 {
+            return Err(HWKeyError::CommunicationError("Response is too short".to_string()))
+        }
+        let sw_pos = response.len() - 2;
+        let sw = (u16::from(response[sw_pos]) << 8) | u16::from(response[sw_pos + 1]);
+        if sw != SW_OK {
+            return Err(status_error(sw))
+        }
+        response.truncate(sw_pos);
+        Ok(response)
+    }
+}
+
+/// Ledger or Trezor connected through USB. Each of them splits the data into HID packets in its own way
+pub struct HidTransport {
+    path: CString,
+    vendor: Vendor
+}
+
+impl HidTransport {
+    /// Lists all connected devices of the vendor, ordered by their HID path
+    pub fn list(vendor: Vendor) -> Result<Vec<HidTransport>, HWKeyError> {
+        let api = HidApi::new()?;
+        let mut paths: Vec<CString> = api.devices().iter()
+            .filter(|d| match vendor {
+                Vendor::Ledger => HidTransport::is_ledger(d),
+                Vendor::Trezor => HidTransport::is_trezor(d)
+            })
+            .map(|d| d.path.clone())
+            .collect();
+        paths.sort();
+        paths.dedup();
+        Ok(paths.into_iter().map(|path| HidTransport { path, vendor }).collect())
+    }
+
+    fn is_ledger(device: &HidDeviceInfo) -> bool {
+        let vendor = device.vendor_id == LEDGER_VENDOR_ID
+            || (device.vendor_id == LEDGER_LEGACY_VENDOR_ID && device.product_id == LEDGER_LEGACY_PRODUCT_ID);
+        // a Ledger has several HID interfaces, and only one of them accepts APDU
+        vendor && (device.usage_page == LEDGER_USAGE_PAGE || device.interface_number == 0)
+    }
+
+    fn is_trezor(device: &HidDeviceInfo) -> bool {
+        let known = (device.vendor_id == TREZOR_VENDOR_ID && device.product_id == TREZOR_PRODUCT_ID)
+            || (device.vendor_id == TREZOR_V2_VENDOR_ID && device.product_id == TREZOR_V2_PRODUCT_ID);
+        known && device.interface_number == TREZOR_INTERFACE
+    }
+
+    fn write_apdu(device: &HidDevice, apdu: &[u8]) -> Result<(), HWKeyError> {
+        let mut data = vec![(apdu.len() >> 8) as u8, apdu.len() as u8];
+        data.extend_from_slice(apdu);
+        // packet header: channel (2 bytes), tag (1), sequence (2)
+        for (seq, chunk) in data.chunks(HID_PACKET_SIZE - 5).enumerate() {
+            // first byte is HID Report ID, always 0 for Ledger
+            let mut packet = vec![0x00, (HID_CHANNEL >> 8) as u8, HID_CHANNEL as u8, HID_TAG_APDU, (seq >> 8) as u8, seq as u8];
+            packet.extend_from_slice(chunk);
+            packet.resize(HID_PACKET_SIZE + 1, 0);
+            device.write(&packet)?;
+        }
+        Ok(())
+    }
+
+    fn read_response(device: &HidDevice) -> Result<Vec<u8>, HWKeyError> {
+        let mut data = vec![];
+        let mut expected_len = None;
+        let mut seq = 0usize;
+        loop {
+            let mut packet = [0u8; HID_PACKET_SIZE];
+            let size = device.read_timeout(&mut packet, HID_READ_TIMEOUT_MS)?;
+            if size == 0 {
+                return Err(HWKeyError::CommunicationError("No response from device".to_string()))
+            }
+            // the first packet has the length of the response after the header
+            let header_size = if seq == 0 { 7 } else { 5 };
+            if size < header_size {
+                return Err(HWKeyError::CommunicationError("Packet is too short".to_string()))
+            }
+            let packet_seq = (usize::from(packet[3]) << 8) | usize::from(packet[4]);
+            if packet[2] != HID_TAG_APDU || packet_seq != seq {
+                return Err(HWKeyError::CommunicationError("Unexpected packet".to_string()))
+            }
+            let body = if seq == 0 {
+                expected_len = Some((usize::from(packet[5]) << 8) | usize::from(packet[6]));
+                &packet[7..size]
+            } else {
+                &packet[5..size]
+            };
+            data.extend_from_slice(body);
+            let len = expected_len.unwrap_or(0);
+            if data.len() >= len {
+                data.truncate(len);
+                return Ok(data)
+            }
+            seq += 1;
+        }
+    }
+
+    /// Writes a Trezor message, which already has its header, in packets starting with `?`
+    fn write_message(device: &HidDevice, message: &[u8]) -> Result<(), HWKeyError> {
+        for chunk in message.chunks(HID_PACKET_SIZE - 1) {
+            // first byte is HID Report ID, always 0 for Trezor
+            let mut packet = vec![0x00, b'?'];
+            packet.extend_from_slice(chunk);
+            packet.resize(HID_PACKET_SIZE + 1, 0);
+            device.write(&packet)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a Trezor message, including its header
+    fn read_message(device: &HidDevice) -> Result<Vec<u8>, HWKeyError> {
+        let mut data = vec![];
+        let mut expected_len = None;
+        loop {
+            let mut packet = [0u8; HID_PACKET_SIZE];
+            let timeout = if expected_len.is_none() { HID_CONFIRM_TIMEOUT_MS } else { HID_READ_TIMEOUT_MS };
+            let size = device.read_timeout(&mut packet, timeout)?;
+            if size < 1 || packet[0] != b'?' {
+                return Err(HWKeyError::CommunicationError("No response from device".to_string()))
+            }
+            if expected_len.is_none() {
+                if size < TREZOR_HEADER_SIZE + 1 || packet[1] != b'#' || packet[2] != b'#' {
+                    return Err(HWKeyError::CommunicationError("Unexpected packet".to_string()))
+                }
+                let len = packet[5..9].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+                expected_len = Some(len.checked_add(TREZOR_HEADER_SIZE)
+                    .ok_or_else(|| HWKeyError::CommunicationError("Invalid message length".to_string()))?);
+            }
+            data.extend_from_slice(&packet[1..size]);
+            let len = expected_len.unwrap_or(0);
+            if data.len() >= len {
+                data.truncate(len);
+                return Ok(data)
+            }
+        }
+    }
+}
+
+impl HwTransport for HidTransport {
+    fn id(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+
+    fn is_connected(&self) -> bool {
+        match HidApi::new() {
+            Ok(api) => api.devices().iter().any(|d| d.path == self.path),
+            Err(_) => false
+        }
+    }
+
+    fn send(&self, data: &[u8]) -> Result<Vec<u8>, HWKeyError> {
+        let api = HidApi::new()?;
+        let device = api.open_path(&self.path).map_err(|_| HWKeyError::NotConnected)?;
+        match self.vendor {
+            Vendor::Ledger => {
+                HidTransport::write_apdu(&device, data)?;
+                HidTransport::read_response(&device)
+            },
+            Vendor::Trezor => {
+                HidTransport::write_message(&device, data)?;
+                HidTransport::read_message(&device)
+            }
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use std::str::FromStr;
+
+use emerald_vault::{Address, Signature, Transaction};
+use hardware::{HWAppJson, HWKey, HWKeyError, Vendor};
+use transport::{HwTransport, TREZOR_HEADER_SIZE};
+
+/// Types of the Trezor protocol messages used here, as defined in `messages.proto` of the Trezor firmware
+pub const MSG_INITIALIZE: u16 = 0;
+pub const MSG_SUCCESS: u16 = 2;
+pub const MSG_FAILURE: u16 = 3;
+pub const MSG_FEATURES: u16 = 17;
+pub const MSG_PIN_MATRIX_REQUEST: u16 = 18;
+pub const MSG_CANCEL: u16 = 20;
+pub const MSG_BUTTON_REQUEST: u16 = 26;
+pub const MSG_BUTTON_ACK: u16 = 27;
+pub const MSG_PASSPHRASE_REQUEST: u16 = 41;
+pub const MSG_ETHEREUM_GET_ADDRESS: u16 = 56;
+pub const MSG_ETHEREUM_ADDRESS: u16 = 57;
+pub const MSG_ETHEREUM_SIGN_TX: u16 = 58;
+pub const MSG_ETHEREUM_TX_REQUEST: u16 = 59;
+pub const MSG_ETHEREUM_TX_ACK: u16 = 60;
+
+/// Codes of a `Failure` message
+pub const FAILURE_ACTION_CANCELLED: u64 = 4;
+pub const FAILURE_PIN_CANCELLED: u64 = 6;
+pub const FAILURE_PIN_INVALID: u64 = 7;
+
+/// Max size of transaction data sent with `EthereumSignTx`, the rest is requested by the device in chunks
+pub const DATA_INITIAL_CHUNK_SIZE: usize = 1024;
+
+/// Name reported as the "app" of a Trezor, which has Ethereum support in the firmware
+pub const TREZOR_APP_NAME: &str = "Trezor";
+
+/// Field value of a Protobuf message. Only the types used by the Ethereum messages are supported
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtoValue {
+    Varint(u64),
+    Bytes(Vec<u8>)
+}
+
+/// Minimal Protobuf message, as a list of fields in the order they're encoded
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProtoMessage {
+    pub fields: Vec<(u32, ProtoValue)>
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let b = *data.get(*pos)?;
+        *pos += 1;
+        if shift > 63 {
+            return None
+        }
+        result |= u64::from(b & 0x7f) << shift;
+        if b & 0x80 == 0 {
+            return Some(result)
+        }
+        shift += 7;
+    }
+}
+
+impl ProtoMessage {
+    pub fn new() -> ProtoMessage {
+        ProtoMessage::default()
+    }
+
+    pub fn with_varint(mut self, field: u32, value: u64) -> ProtoMessage {
+        self.fields.push((field, ProtoValue::Varint(value)));
+        self
+    }
+
+    pub fn with_bytes(mut self, field: u32, value: &[u8]) -> ProtoMessage {
+        self.fields.push((field, ProtoValue::Bytes(value.to_vec())));
+        self
+    }
+
+    pub fn with_string(self, field: u32, value: &str) -> ProtoMessage {
+        self.with_bytes(field, value.as_bytes())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        for (field, value) in &self.fields {
+            match value {
+                ProtoValue::Varint(v) => {
+                    write_varint(&mut buf, u64::from(*field) << 3);
+                    write_varint(&mut buf, *v);
+                },
+                ProtoValue::Bytes(v) => {
+                    write_varint(&mut buf, (u64::from(*field) << 3) | 2);
+                    write_varint(&mut buf, v.len() as u64);
+                    buf.extend_from_slice(v);
+                }
+            }
+        }
+        buf
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<ProtoMessage, HWKeyError> {
+        let invalid = || HWKeyError::CommunicationError("Invalid message".to_string());
+        let mut fields = vec![];
+        let mut pos = 0;
+        while pos < data.len() {
+            let key = read_varint(data, &mut pos).ok_or_else(invalid)?;
+            let field = (key >> 3) as u32;
+            let value = match key & 0x07 {
+                0 => ProtoValue::Varint(read_varint(data, &mut pos).ok_or_else(invalid)?),
+                2 => {
+                    let len = read_varint(data, &mut pos).ok_or_else(invalid)? as usize;
+                    let end = pos.checked_add(len).ok_or_else(invalid)?;
+                    let value = data.get(pos..end).ok_or_else(invalid)?;
+                    pos = end;
+                    ProtoValue::Bytes(value.to_vec())
+                },
+                // fixed size values aren't used by the supported messages, but skip them to stay compatible
+                1 => {
+                    pos += 8;
+                    continue
+                },
+                5 => {
+                    pos += 4;
+                    continue
+                },
+                _ => return Err(invalid())
+            };
+            fields.push((field, value));
+        }
+        Ok(ProtoMessage { fields })
+    }
+
+    pub fn get_varint(&self, field: u32) -> Option<u64> {
+        self.fields.iter().find_map(|(f, v)| match v {
+            ProtoValue::Varint(value) if *f == field => Some(*value),
+            _ => None
+        })
+    }
+
+    pub fn get_bytes(&self, field: u32) -> Option<&[u8]> {
+        self.fields.iter().find_map(|(f, v)| match v {
+            ProtoValue::Bytes(value) if *f == field => Some(value.as_slice()),
+            _ => None
+        })
+    }
+
+    pub fn get_string(&self, field: u32) -> Option<String> {
+        self.get_bytes(field).map(|v| String::from_utf8_lossy(v).to_string())
+    }
+
+    /// All values of a repeated varint field, either packed or not
+    #[cfg(feature = "hw-emulator")]
+    pub fn get_repeated(&self, field: u32) -> Vec<u64> {
+        let mut result = vec![];
+        for (f, v) in &self.fields {
+            if *f != field {
+                continue
+            }
+            match v {
+                ProtoValue::Varint(value) => result.push(*value),
+                ProtoValue::Bytes(packed) => {
+                    let mut pos = 0;
+                    while let Some(value) = read_varint(packed, &mut pos) {
+                        result.push(value);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Big-endian bytes of a number without leading zeroes, as Trezor expects for the transaction values
+fn trim_bytes(value: &[u8]) -> &[u8] {
+    let start = value.iter().position(|b| *b != 0).unwrap_or(value.len());
+    &value[start..]
+}
+
+/// Message with the header, as it's sent through the transport: magic `##`, type (2 bytes), payload length
+/// (4 bytes) and the payload
+pub fn encode_message(msg_type: u16, payload: &[u8]) -> Vec<u8> {
+    let len = payload.len();
+    let mut data = vec![b'#', b'#', (msg_type >> 8) as u8, msg_type as u8,
+                        (len >> 24) as u8, (len >> 16) as u8, (len >> 8) as u8, len as u8];
+    data.extend_from_slice(payload);
+    data
+}
+
+/// Type and payload of a message received through the transport
+pub fn decode_message(data: &[u8]) -> Result<(u16, Vec<u8>), HWKeyError> {
+    if data.len() < TREZOR_HEADER_SIZE || data[0] != b'#' || data[1] != b'#' {
+        return Err(HWKeyError::CommunicationError("Invalid message".to_string()))
+    }
+    let msg_type = (u16::from(data[2]) << 8) | u16::from(data[3]);
+    let len = data[4..8].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+    if data.len() - TREZOR_HEADER_SIZE != len {
+        return Err(HWKeyError::CommunicationError("Invalid message length".to_string()))
+    }
+    Ok((msg_type, data[TREZOR_HEADER_SIZE..].to_vec()))
+}
+
+/// Trezor, accessed through a transport
+pub struct TrezorKey {
+    transport: Box<dyn HwTransport>
+}
+
+impl TrezorKey {
+    pub fn new(transport: Box<dyn HwTransport>) -> TrezorKey {
+        TrezorKey { transport }
+    }
+
+    fn exchange(&self, msg_type: u16, payload: &[u8]) -> Result<(u16, Vec<u8>), HWKeyError> {
+        let response = self.transport.send(&encode_message(msg_type, payload))?;
+        decode_message(&response)
+    }
+
+    /// Sends the message and returns the response of the expected type. Confirms button requests, which means
+    /// the device waits for the user, and converts failures and PIN requests to errors
+    fn call(&self, msg_type: u16, msg: &ProtoMessage, expected: u16) -> Result<ProtoMessage, HWKeyError> {
+        let (mut resp_type, mut payload) = self.exchange(msg_type, &msg.to_bytes())?;
+        loop {
+            match resp_type {
+                t if t == expected => return ProtoMessage::from_bytes(&payload),
+                MSG_BUTTON_REQUEST => {
+                    let next = self.exchange(MSG_BUTTON_ACK, &[])?;
+                    resp_type = next.0;
+                    payload = next.1;
+                },
+                MSG_PIN_MATRIX_REQUEST | MSG_PASSPHRASE_REQUEST => {
+                    // PIN is entered on the host with a scrambled matrix, which isn't supported. Reset the device
+                    // state so it doesn't wait for the PIN
+                    let _ = self.exchange(MSG_CANCEL, &[]);
+                    return Err(HWKeyError::Locked)
+                },
+                MSG_FAILURE => {
+                    let failure = ProtoMessage::from_bytes(&payload)?;
+                    return Err(match failure.get_varint(1) {
+                        Some(FAILURE_ACTION_CANCELLED) => HWKeyError::Rejected,
+                        Some(FAILURE_PIN_CANCELLED) | Some(FAILURE_PIN_INVALID) => HWKeyError::Locked,
+                        Some(code) => HWKeyError::DeviceError(code as u16),
+                        None => HWKeyError::CommunicationError(failure.get_string(2).unwrap_or_default())
+                    })
+                },
+                _ => return Err(HWKeyError::CommunicationError(format!("Unexpected message {}", resp_type)))
+            }
+        }
+    }
+
+    fn get_features(&self) -> Result<ProtoMessage, HWKeyError> {
+        self.call(MSG_INITIALIZE, &ProtoMessage::new(), MSG_FEATURES)
+    }
+
+    fn request_address(&self, hd_path: &[u32], show: bool) -> Result<Address, HWKeyError> {
+        let mut msg = ProtoMessage::new();
+        for e in hd_path {
+            msg = msg.with_varint(1, u64::from(*e));
+        }
+        msg = msg.with_varint(2, show as u64);
+        let response = self.call(MSG_ETHEREUM_GET_ADDRESS, &msg, MSG_ETHEREUM_ADDRESS)?;
+        let invalid = || HWKeyError::CommunicationError("Invalid address response".to_string());
+        // newer firmware returns a string with checksum, older returns 20 bytes
+        let address = match response.get_string(2) {
+            Some(address) => address,
+            None => format!("0x{}", hex::encode(response.get_bytes(1).ok_or_else(invalid)?))
+        };
+        Address::from_str(address.as_str()).map_err(|_| invalid())
+    }
+}
+
+impl HWKey for TrezorKey {
+    fn id(&self) -> String {
+        self.transport.id()
+    }
+
+    fn vendor(&self) -> Vendor {
+        Vendor::Trezor
+    }
+
+    fn is_connected(&self) -> bool {
+        self.transport.is_connected()
+    }
+
+    fn get_app_version(&self) -> Result<String, HWKeyError> {
+        let features = self.get_features()?;
+        Ok(format!("{}.{}.{}",
+                   features.get_varint(2).unwrap_or(0),
+                   features.get_varint(3).unwrap_or(0),
+                   features.get_varint(4).unwrap_or(0)))
+    }
+
+    fn get_app_state(&self) -> HWAppJson {
+        if !self.is_connected() {
+            return HWAppJson::disconnected()
+        }
+        let mut result = HWAppJson {
+            connected: true,
+            ..HWAppJson::disconnected()
+        };
+        match self.get_features() {
+            Ok(features) => {
+                let initialized = features.get_varint(12).unwrap_or(0) != 0;
+                // older firmware doesn't report if it's unlocked, but reports if the PIN is set
+                let unlocked = match features.get_varint(16) {
+                    Some(value) => value != 0,
+                    None => features.get_varint(7).unwrap_or(0) == 0
+                };
+                result.locked = !unlocked;
+                result.ethereum = initialized && unlocked;
+                result.app = Some(TREZOR_APP_NAME.to_string());
+                result.version = self.get_app_version().ok();
+            },
+            Err(HWKeyError::Locked) => result.locked = true,
+            Err(HWKeyError::NotConnected) => result.connected = false,
+            Err(_) => {}
+        }
+        result
+    }
+
+    fn get_address(&self, hd_path: &[u32]) -> Result<Address, HWKeyError> {
+        self.request_address(hd_path, false)
+    }
+
+    fn confirm_address(&self, hd_path: &[u32]) -> Result<Address, HWKeyError> {
+        self.request_address(hd_path, true)
+    }
+
+    /// Sends transaction fields with the first chunk of data, and the rest of data as requested by the device
+    fn sign_transaction(&self, hd_path: &[u32], tx: &Transaction, chain_id: u8) -> Result<Signature, HWKeyError> {
+        let mut msg = ProtoMessage::new();
+        for e in hd_path {
+            msg = msg.with_varint(1, u64::from(*e));
+        }
+        msg = msg
+            .with_bytes(2, trim_bytes(&tx.nonce.to_be_bytes()))
+            .with_bytes(3, trim_bytes(&tx.gas_price))
+            .with_bytes(4, trim_bytes(&tx.gas_limit.to_be_bytes()))
+            .with_bytes(6, trim_bytes(&tx.value));
+        if let Some(to) = &tx.to {
+            msg = msg.with_string(11, to.to_string().as_str());
+        }
+        let data = &tx.data;
+        if !data.is_empty() {
+            let initial = data.len().min(DATA_INITIAL_CHUNK_SIZE);
+            msg = msg
+                .with_bytes(7, &data[..initial])
+                .with_varint(8, data.len() as u64);
+        }
+        msg = msg.with_varint(9, u64::from(chain_id));
+
+        let mut sent = data.len().min(DATA_INITIAL_CHUNK_SIZE);
+        let mut response = self.call(MSG_ETHEREUM_SIGN_TX, &msg, MSG_ETHEREUM_TX_REQUEST)?;
+        while let Some(requested) = response.get_varint(1) {
+            let requested = requested as usize;
+            if requested == 0 || sent + requested > data.len() {
+                return Err(HWKeyError::CommunicationError("Invalid data request".to_string()))
+            }
+            let ack = ProtoMessage::new().with_bytes(1, &data[sent..sent + requested]);
+            sent += requested;
+            response = self.call(MSG_ETHEREUM_TX_ACK, &ack, MSG_ETHEREUM_TX_REQUEST)?;
+        }
+
+        let invalid = || HWKeyError::CommunicationError("Invalid signature response".to_string());
+        let v = response.get_varint(2).ok_or_else(invalid)?;
+        let r = response.get_bytes(3).ok_or_else(invalid)?;
+        let s = response.get_bytes(4).ok_or_else(invalid)?;
+        if r.len() != 32 || s.len() != 32 {
+            return Err(invalid())
+        }
+        // device returns the full EIP-155 value, i.e. `chain_id * 2 + 35 + recovery`
+        let v = match v {
+            27 | 28 => v as u8,
+            _ => {
+                let recovery = v.checked_sub(u64::from(chain_id) * 2 + 35).ok_or_else(invalid)?;
+                if recovery > 1 {
+                    return Err(invalid())
+                }
+                27 + recovery as u8
+            }
+        };
+        let mut sig = Signature { v, r: [0u8; 32], s: [0u8; 32] };
+        sig.r.copy_from_slice(r);
+        sig.s.copy_from_slice(s);
+        Ok(sig)
+    }
+}
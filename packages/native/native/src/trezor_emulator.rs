@@ -0,0 +1,250 @@
+use std::cell::RefCell;
+use std::str::FromStr;
+
+use emerald_vault::{Address, Transaction};
+use emulator::EmulatedSeed;
+use hardware::{EmulatorState, HWKeyError};
+use trezor::{
+    decode_message,
+    encode_message,
+    ProtoMessage,
+    DATA_INITIAL_CHUNK_SIZE,
+    FAILURE_ACTION_CANCELLED,
+    MSG_BUTTON_ACK,
+    MSG_BUTTON_REQUEST,
+    MSG_CANCEL,
+    MSG_ETHEREUM_ADDRESS,
+    MSG_ETHEREUM_GET_ADDRESS,
+    MSG_ETHEREUM_SIGN_TX,
+    MSG_ETHEREUM_TX_ACK,
+    MSG_ETHEREUM_TX_REQUEST,
+    MSG_FAILURE,
+    MSG_FEATURES,
+    MSG_INITIALIZE,
+    MSG_PIN_MATRIX_REQUEST
+};
+use transport::HwTransport;
+
+/// Firmware version reported by the emulator
+const FIRMWARE_VERSION: [u64; 3] = [1, 9, 0];
+/// `Failure` code for a message which isn't expected in the current state
+const FAILURE_UNEXPECTED_MESSAGE: u64 = 1;
+const FAILURE_DATA_ERROR: u64 = 3;
+/// `ButtonRequest` code shown before signing a transaction
+const BUTTON_SIGN_TX: u64 = 8;
+/// `ButtonRequest` code shown with an address
+const BUTTON_ADDRESS: u64 = 10;
+
+/// Transaction which is being received by the emulator
+struct SigningTx {
+    hd_path: Vec<u32>,
+    tx: Transaction,
+    chain_id: u8,
+    data_length: usize
+}
+
+/// Operation which waits for the user to press a button on the device
+enum Confirmation {
+    Address(Vec<u32>),
+    Sign(SigningTx)
+}
+
+/// Emulates Trezor on the level of the protocol messages, with a fixed test seed. Allows to test the Trezor protocol
+/// without a device
+pub struct TrezorEmulator {
+    index: usize,
+    seed: EmulatedSeed,
+    state: EmulatorState,
+    signing: RefCell<Option<SigningTx>>,
+    confirmation: RefCell<Option<Confirmation>>
+}
+
+fn failure(code: u64, message: &str) -> (u16, ProtoMessage) {
+    (MSG_FAILURE, ProtoMessage::new().with_varint(1, code).with_string(2, message))
+}
+
+fn to_u64(value: &[u8]) -> u64 {
+    value.iter().fold(0, |acc, b| (acc << 8) | u64::from(*b))
+}
+
+fn to_u256(value: &[u8]) -> Option<[u8; 32]> {
+    if value.len() > 32 {
+        return None
+    }
+    let mut result = [0u8; 32];
+    result[32 - value.len()..].copy_from_slice(value);
+    Some(result)
+}
+
+impl TrezorEmulator {
+    /// Creates emulator of a device with the seed at `index` of `emulator::EMULATOR_MNEMONICS`
+    pub fn new(index: usize, state: EmulatorState) -> TrezorEmulator {
+        TrezorEmulator {
+            index,
+            seed: EmulatedSeed::new(index),
+            state,
+            signing: RefCell::new(None),
+            confirmation: RefCell::new(None)
+        }
+    }
+
+    fn features(&self) -> (u16, ProtoMessage) {
+        let locked = self.state == EmulatorState::Locked;
+        let msg = ProtoMessage::new()
+            .with_string(1, "trezor.io")
+            .with_varint(2, FIRMWARE_VERSION[0])
+            .with_varint(3, FIRMWARE_VERSION[1])
+            .with_varint(4, FIRMWARE_VERSION[2])
+            .with_string(6, format!("EMULATOR{}", self.index).as_str())
+            .with_varint(7, locked as u64)
+            .with_string(10, format!("Emulator {}", self.index).as_str())
+            .with_varint(12, 1)
+            .with_varint(16, !locked as u64)
+            .with_string(21, "1");
+        (MSG_FEATURES, msg)
+    }
+
+    fn address(&self, hd_path: &[u32]) -> (u16, ProtoMessage) {
+        match self.seed.get_address(hd_path) {
+            Some(address) => (MSG_ETHEREUM_ADDRESS, ProtoMessage::new().with_string(2, address.to_string().as_str())),
+            None => failure(FAILURE_DATA_ERROR, "Invalid path")
+        }
+    }
+
+    fn get_address(&self, msg: &ProtoMessage) -> (u16, ProtoMessage) {
+        let hd_path: Vec<u32> = msg.get_repeated(1).iter().map(|e| *e as u32).collect();
+        if msg.get_varint(2).unwrap_or(0) != 0 {
+            *self.confirmation.borrow_mut() = Some(Confirmation::Address(hd_path));
+            return (MSG_BUTTON_REQUEST, ProtoMessage::new().with_varint(1, BUTTON_ADDRESS))
+        }
+        self.address(&hd_path)
+    }
+
+    fn sign_tx(&self, msg: &ProtoMessage) -> (u16, ProtoMessage) {
+        let to = match msg.get_string(11) {
+            Some(to) => match Address::from_str(to.as_str()) {
+                Ok(to) => Some(to),
+                Err(_) => return failure(FAILURE_DATA_ERROR, "Invalid recipient")
+            },
+            None => None
+        };
+        let (gas_price, value) = match (to_u256(msg.get_bytes(3).unwrap_or(&[])), to_u256(msg.get_bytes(6).unwrap_or(&[]))) {
+            (Some(gas_price), Some(value)) => (gas_price, value),
+            _ => return failure(FAILURE_DATA_ERROR, "Invalid value")
+        };
+        let data = msg.get_bytes(7).unwrap_or(&[]).to_vec();
+        let data_length = msg.get_varint(8).unwrap_or(0) as usize;
+        if data.len() > DATA_INITIAL_CHUNK_SIZE || data.len() > data_length {
+            return failure(FAILURE_DATA_ERROR, "Invalid data")
+        }
+        let signing = SigningTx {
+            hd_path: msg.get_repeated(1).iter().map(|e| *e as u32).collect(),
+            tx: Transaction {
+                nonce: to_u64(msg.get_bytes(2).unwrap_or(&[])),
+                gas_price,
+                gas_limit: to_u64(msg.get_bytes(4).unwrap_or(&[])),
+                to,
+                value,
+                data
+            },
+            chain_id: msg.get_varint(9).unwrap_or(0) as u8,
+            data_length
+        };
+        self.next_chunk(signing)
+    }
+
+    fn tx_ack(&self, msg: &ProtoMessage) -> (u16, ProtoMessage) {
+        let mut signing = match self.signing.borrow_mut().take() {
+            Some(signing) => signing,
+            None => return failure(FAILURE_UNEXPECTED_MESSAGE, "Unexpected message")
+        };
+        signing.tx.data.extend_from_slice(msg.get_bytes(1).unwrap_or(&[]));
+        if signing.tx.data.len() > signing.data_length {
+            return failure(FAILURE_DATA_ERROR, "Too much data")
+        }
+        self.next_chunk(signing)
+    }
+
+    /// Requests the next chunk of data, or asks user to confirm the transaction when the whole data is received
+    fn next_chunk(&self, signing: SigningTx) -> (u16, ProtoMessage) {
+        let remaining = signing.data_length.saturating_sub(signing.tx.data.len());
+        if remaining > 0 {
+            *self.signing.borrow_mut() = Some(signing);
+            return (MSG_ETHEREUM_TX_REQUEST, ProtoMessage::new().with_varint(1, remaining.min(DATA_INITIAL_CHUNK_SIZE) as u64))
+        }
+        *self.confirmation.borrow_mut() = Some(Confirmation::Sign(signing));
+        (MSG_BUTTON_REQUEST, ProtoMessage::new().with_varint(1, BUTTON_SIGN_TX))
+    }
+
+    /// Signature is returned with `v` which includes the EIP-155 Chain Id
+    fn sign(&self, signing: SigningTx) -> (u16, ProtoMessage) {
+        let rlp = signing.tx.to_rlp(Some(signing.chain_id));
+        let signature = match self.seed.sign(&signing.hd_path, &rlp) {
+            Some(signature) => signature,
+            None => return failure(FAILURE_DATA_ERROR, "Invalid path")
+        };
+        let v = u64::from(signing.chain_id) * 2 + 35 + u64::from(signature.v - 27);
+        let msg = ProtoMessage::new()
+            .with_varint(2, v)
+            .with_bytes(3, &signature.r)
+            .with_bytes(4, &signature.s);
+        (MSG_ETHEREUM_TX_REQUEST, msg)
+    }
+
+    fn button_ack(&self) -> (u16, ProtoMessage) {
+        let confirmation = match self.confirmation.borrow_mut().take() {
+            Some(confirmation) => confirmation,
+            None => return failure(FAILURE_UNEXPECTED_MESSAGE, "Unexpected message")
+        };
+        if self.state == EmulatorState::Rejecting {
+            return failure(FAILURE_ACTION_CANCELLED, "Cancelled")
+        }
+        match confirmation {
+            Confirmation::Address(hd_path) => self.address(&hd_path),
+            Confirmation::Sign(signing) => self.sign(signing)
+        }
+    }
+
+    fn process(&self, msg_type: u16, msg: &ProtoMessage) -> (u16, ProtoMessage) {
+        match msg_type {
+            MSG_INITIALIZE => {
+                *self.signing.borrow_mut() = None;
+                *self.confirmation.borrow_mut() = None;
+                return self.features()
+            },
+            MSG_CANCEL => {
+                *self.signing.borrow_mut() = None;
+                *self.confirmation.borrow_mut() = None;
+                return failure(FAILURE_ACTION_CANCELLED, "Cancelled")
+            },
+            _ => {}
+        }
+        if self.state == EmulatorState::Locked {
+            return (MSG_PIN_MATRIX_REQUEST, ProtoMessage::new().with_varint(1, 1))
+        }
+        match msg_type {
+            MSG_ETHEREUM_GET_ADDRESS => self.get_address(msg),
+            MSG_ETHEREUM_SIGN_TX => self.sign_tx(msg),
+            MSG_ETHEREUM_TX_ACK => self.tx_ack(msg),
+            MSG_BUTTON_ACK => self.button_ack(),
+            _ => failure(FAILURE_UNEXPECTED_MESSAGE, "Unexpected message")
+        }
+    }
+}
+
+impl HwTransport for TrezorEmulator {
+    fn id(&self) -> String {
+        format!("trezor-emulator:{}", self.index)
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn send(&self, data: &[u8]) -> Result<Vec<u8>, HWKeyError> {
+        let (msg_type, payload) = decode_message(data)?;
+        let msg = ProtoMessage::from_bytes(&payload)?;
+        let (resp_type, resp) = self.process(msg_type, &msg);
+        Ok(encode_message(resp_type, &resp.to_bytes()))
+    }
+}
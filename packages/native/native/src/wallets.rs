@@ -1,15 +1,23 @@
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::str::FromStr;
 
-use neon::prelude::{FunctionContext, JsObject, JsResult, JsString, JsNumber};
+use neon::prelude::{FunctionContext, JsArray, JsObject, JsResult, JsString, JsNumber};
 use uuid::Uuid;
 
 use access::{VaultConfig, WrappedVault, args_get_str};
 use emerald_vault::{convert::{
     json::keyfile::EthereumJsonV3File
-}, core::chains::Blockchain, mnemonic::HDPath, storage::error::VaultError, trim_hex, structs::wallet::Wallet, PrivateKey};
-use json::StatusResult;
-use emerald_vault::structs::wallet::AccountId;
+}, core::chains::Blockchain, mnemonic::HDPath, storage::error::VaultError, trim_hex, structs::wallet::Wallet, PrivateKey, Address};
+use hardware::{decode_hd_path, format_hd_path, parse_hd_path};
+use json::{StatusResult, deserialize_some, status_code};
+use meta::{now_millis, AccountMeta, WalletMeta};
+use emerald_vault::structs::{
+    seed::{SeedRef, SeedSource},
+    wallet::{AccountId, PKType, WalletAccount}
+};
+use emerald_vault::util::optional::none_if_empty;
+use seeds::list_xpub_addresses;
 
 #[derive(Deserialize, Clone)]
 pub struct AddAccountJson {
@@ -29,7 +37,9 @@ pub enum AddAccountType {
     #[serde(rename = "hd-path")]
     HdPath(SeedAccount),
     #[serde(rename = "generate-random")]
-    GenerateRandom
+    GenerateRandom,
+    #[serde(rename = "watch-only")]
+    WatchOnly(WatchOnlyAccount)
 }
 
 #[derive(Deserialize, Clone)]
@@ -38,41 +48,234 @@ pub struct SeedAccount {
     pub seed_id: String,
     #[serde(rename = "hdPath")]
     pub hd_path: String,
-    pub password: String,
+    /// Not required for a Hardware Wallet seed
+    pub password: Option<String>,
     pub address: Option<String>
 }
 
+/// Account without a key, either a single address or a range of addresses `<xpub>/0/i` derived
+/// from an account-level xpub. A range is a one-time import, each address becomes a separate
+/// watch-only account and the xpub itself is not stored
+#[derive(Deserialize, Clone)]
+pub struct WatchOnlyAccount {
+    pub address: Option<String>,
+    pub xpub: Option<String>,
+    pub start: Option<u32>,
+    pub count: Option<u32>
+}
+
 #[derive(Serialize, Clone)]
 pub struct WalletAccountJson {
     pub id: String,
     pub blockchain: u32,
     pub address: Option<String>,
+    #[serde(rename = "watchOnly")]
+    pub watch_only: bool,
+    pub key: AccountKeyJson,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub hidden: bool
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub enum AccountKeyType {
+    #[serde(rename = "raw-pk")]
+    RawPk,
+    #[serde(rename = "seed-hd")]
+    SeedHd,
+    #[serde(rename = "ledger")]
+    Ledger,
+    #[serde(rename = "watch-only")]
+    WatchOnly
+}
+
+/// Describes where the key of an account is, without any secret data
+#[derive(Serialize, Clone)]
+pub struct AccountKeyJson {
+    #[serde(rename = "type")]
+    pub key_type: AccountKeyType,
+    /// Id of the key in the vault, for `raw-pk`
+    #[serde(rename = "keyId")]
+    pub key_id: Option<String>,
+    /// For a key derived from a seed, `seed-hd` or `ledger`
+    #[serde(rename = "seedId")]
+    pub seed_id: Option<String>,
+    #[serde(rename = "hdPath")]
+    pub hd_path: Option<String>
+}
+
+impl AccountKeyJson {
+    /// `ledger_seeds` are ids of the Hardware Wallet seeds, which are referenced with the `ledger` type
+    fn new(account: &WalletAccount, ledger_seeds: &HashSet<Uuid>) -> AccountKeyJson {
+        if is_watch_only(account) {
+            return AccountKeyJson {
+                key_type: AccountKeyType::WatchOnly,
+                key_id: None,
+                seed_id: None,
+                hd_path: None
+            }
+        }
+        match &account.key {
+            PKType::PrivateKeyRef(id) => AccountKeyJson {
+                key_type: AccountKeyType::RawPk,
+                key_id: Some(id.to_string()),
+                seed_id: None,
+                hd_path: None
+            },
+            PKType::SeedHd(seed) => AccountKeyJson {
+                key_type: if ledger_seeds.contains(&seed.seed_id) { AccountKeyType::Ledger } else { AccountKeyType::SeedHd },
+                key_id: None,
+                seed_id: Some(seed.seed_id.to_string()),
+                hd_path: decode_hd_path(&seed.hd_path.to_bytes()).map(|(path, _)| format_hd_path(&path))
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Clone)]
 pub struct WalletJson {
     pub id: String,
     pub name: Option<String>,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<u64>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<u64>,
+    pub order: Option<i32>,
     pub accounts: Vec<WalletAccountJson>
 }
 
-impl From<Wallet> for WalletJson {
-    fn from(wallet: Wallet) -> Self {
+/// Changes of a wallet. A missing field keeps the current value, `null` clears it
+#[derive(Deserialize, Debug, Default)]
+pub struct WalletPatch {
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub color: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub icon: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub order: Option<Option<i32>>
+}
+
+impl WalletPatch {
+    /// Applies the changes of the details, the name is set on the wallet by `update_wallet`
+    fn apply(self, meta: &mut WalletMeta) {
+        let clean = |value: Option<String>| value.and_then(|s| none_if_empty(s.as_str()));
+        if let Some(description) = self.description {
+            meta.description = clean(description);
+        }
+        if let Some(color) = self.color {
+            meta.color = clean(color);
+        }
+        if let Some(icon) = self.icon {
+            meta.icon = clean(icon);
+        }
+        if let Some(order) = self.order {
+            meta.order = order;
+        }
+    }
+}
+
+/// Changes of an account. A missing field keeps the current value, `null` clears it
+#[derive(Deserialize, Debug, Default)]
+pub struct AccountPatch {
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub label: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub description: Option<Option<String>>,
+    pub hidden: Option<bool>
+}
+
+impl AccountPatch {
+    fn apply(self, meta: &mut AccountMeta) {
+        let clean = |value: Option<String>| value.and_then(|s| none_if_empty(s.as_str()));
+        if let Some(label) = self.label {
+            meta.label = clean(label);
+        }
+        if let Some(description) = self.description {
+            meta.description = clean(description);
+        }
+        if let Some(hidden) = self.hidden {
+            meta.hidden = hidden;
+        }
+    }
+}
+
+impl WalletJson {
+    fn new(wallet: Wallet, meta: WalletMeta, ledger_seeds: &HashSet<Uuid>) -> Self {
         let accounts: Vec<WalletAccountJson> = wallet.accounts.iter()
-            .map(|a| WalletAccountJson {
-                id: AccountId::from(&wallet, a).to_string(),
-                blockchain: a.blockchain as u32,
-                address: a.address.map(|v| v.to_string())
+            .map(|a| {
+                let account_meta = meta.get_account(a.id);
+                WalletAccountJson {
+                    id: AccountId::from(&wallet, a).to_string(),
+                    blockchain: a.blockchain as u32,
+                    address: a.address.map(|v| v.to_string()),
+                    watch_only: is_watch_only(a),
+                    key: AccountKeyJson::new(a, ledger_seeds),
+                    label: account_meta.label,
+                    description: account_meta.description,
+                    hidden: account_meta.hidden
+                }
             })
             .collect();
         WalletJson {
             id: wallet.id.clone().to_string(),
             name: wallet.label,
+            description: meta.description,
+            color: meta.color,
+            icon: meta.icon,
+            created_at: meta.created_at,
+            updated_at: meta.updated_at,
+            order: meta.order,
             accounts
         }
     }
 }
 
+/// Key of a watch-only account. The storage format requires a key for each account, and a key can't have the nil
+/// id, so the reference itself marks the account as watch-only, in the vault and without any other details
+fn watch_only_key() -> PKType {
+    PKType::PrivateKeyRef(Uuid::nil())
+}
+
+/// Account has only an address and no key, i.e. it refers to the key of `watch_only_key`
+pub fn is_watch_only(account: &WalletAccount) -> bool {
+    match account.key {
+        PKType::PrivateKeyRef(id) => id.is_nil(),
+        PKType::SeedHd(_) => false
+    }
+}
+
+/// Id for a new watch-only or Hardware Wallet account, after all existing accounts and all removed ones
+fn next_account_id(wallet: &Wallet, meta: &WalletMeta) -> usize {
+    wallet.accounts.iter()
+        .map(|a| a.id + 1)
+        .max()
+        .unwrap_or(0)
+        .max(meta.next_account_id)
+}
+
+impl WatchOnlyAccount {
+    fn list_addresses(&self) -> Result<Vec<Address>, VaultError> {
+        match (&self.address, &self.xpub) {
+            (Some(address), None) => {
+                let address = Address::from_str(address.as_str())
+                    .map_err(|_| VaultError::InvalidDataError("address".to_string()))?;
+                Ok(vec![address])
+            },
+            (None, Some(xpub)) => {
+                list_xpub_addresses(xpub.as_str(), self.start.unwrap_or(0), self.count.unwrap_or(1))
+            },
+            _ => Err(VaultError::InvalidDataError("Either address or xpub is required".to_string()))
+        }
+    }
+}
+
 fn read_wallet_id(cx: &mut FunctionContext, pos: i32) -> Uuid {
     let wallet_id = cx.argument::<JsString>(pos).expect("wallet_id is not provided").value();
     let wallet_id = Uuid::parse_str(wallet_id.as_str()).expect("Invalid UUID");
@@ -89,6 +292,38 @@ fn read_wallet_and_account_ids(cx: &mut FunctionContext, pos: i32) -> (Uuid, usi
     (wallet_id, account_id)
 }
 
+fn read_wallet_ids(cx: &mut FunctionContext, pos: i32) -> Vec<Uuid> {
+    cx.argument::<JsArray>(pos)
+        .expect("List of wallet ids is not provided")
+        .to_vec(cx)
+        .expect("Failed to convert to Rust vector")
+        .into_iter()
+        .map(|item| {
+            let id = item.downcast::<JsString>().expect("Expected string element in array").value();
+            Uuid::parse_str(id.as_str()).expect("Invalid UUID")
+        })
+        .collect()
+}
+
+/// Parses full id of an account, i.e. `<wallet-id>-<account-id>` as it's produced by `AccountId`
+fn parse_account_id(value: &str) -> Result<(Uuid, usize), VaultError> {
+    let invalid = || VaultError::InvalidDataError(format!("Invalid account id {}", value));
+    let pos = value.rfind('-').ok_or_else(invalid)?;
+    let wallet_id = Uuid::parse_str(&value[..pos]).map_err(|_| invalid())?;
+    let account_id = value[pos + 1..].parse::<usize>().map_err(|_| invalid())?;
+    Ok((wallet_id, account_id))
+}
+
+fn read_account_ids(cx: &mut FunctionContext, pos: i32) -> Vec<String> {
+    cx.argument::<JsArray>(pos)
+        .expect("List of account ids is not provided")
+        .to_vec(cx)
+        .expect("Failed to convert to Rust vector")
+        .into_iter()
+        .map(|item| item.downcast::<JsString>().expect("Expected string element in array").value())
+        .collect()
+}
+
 impl WrappedVault {
 
     fn create_wallet(&self, label: Option<String>) -> Result<Uuid, VaultError> {
@@ -98,7 +333,284 @@ impl WrappedVault {
             id: id.clone(),
             label,
             accounts: vec![]
-        }).map(|_| id)
+        })?;
+        let now = now_millis();
+        self.update_meta(|m| {
+            let meta = m.wallets.entry(id).or_insert_with(WalletMeta::default);
+            meta.created_at = Some(now);
+            meta.updated_at = Some(now);
+        })?;
+        Ok(id)
+    }
+
+    /// Sets modification time of the wallet to now
+    fn touch_wallet(&self, wallet_id: Uuid) -> Result<(), VaultError> {
+        self.update_meta(|m| {
+            m.wallets.entry(wallet_id).or_insert_with(WalletMeta::default).updated_at = Some(now_millis());
+        })
+    }
+
+    /// Wallets ordered by their `order`, followed by wallets without it in the storage order. Hidden accounts are
+    /// included only if the vault is configured to show them
+    fn list_wallets(&self) -> Result<Vec<WalletJson>, VaultError> {
+        let meta = self.read_meta()?;
+        let show_hidden = self.cfg.show_hidden;
+        let ledger_seeds: HashSet<Uuid> = self.list_seeds()?
+            .into_iter()
+            .filter(|s| match s.source {
+                SeedSource::Ledger(_) => true,
+                SeedSource::Bytes(_) => false
+            })
+            .map(|s| s.id)
+            .collect();
+        let mut result: Vec<WalletJson> = self.load_wallets().into_iter()
+            .map(|w| {
+                let wallet_meta = meta.get_wallet(&w.id);
+                let mut json = WalletJson::new(w, wallet_meta, &ledger_seeds);
+                if !show_hidden {
+                    json.accounts.retain(|a| !a.hidden);
+                }
+                json
+            })
+            .collect();
+        result.sort_by_key(|w| (w.order.is_none(), w.order));
+        Ok(result)
+    }
+
+    fn update_wallet(&self, wallet_id: Uuid, mut patch: WalletPatch) -> Result<bool, VaultError> {
+        let storage = &self.cfg.get_storage();
+        let mut wallet = match storage.wallets().get(&wallet_id) {
+            Ok(wallet) => wallet,
+            Err(_) => return Ok(false)
+        };
+        if let Some(name) = patch.name.take() {
+            wallet.label = name.and_then(|s| none_if_empty(s.as_str()));
+            storage.wallets().update(wallet)?;
+        }
+        // patch the current details, so a concurrent change of the accounts is kept
+        self.update_meta(|m| {
+            let meta = m.wallets.entry(wallet_id).or_insert_with(WalletMeta::default);
+            patch.apply(meta);
+            meta.updated_at = Some(now_millis());
+        })?;
+        Ok(true)
+    }
+
+    fn update_account(&self, wallet_id: Uuid, account_id: usize, patch: AccountPatch) -> Result<bool, VaultError> {
+        let storage = &self.cfg.get_storage();
+        let wallet = match storage.wallets().get(&wallet_id) {
+            Ok(wallet) => wallet,
+            Err(_) => return Ok(false)
+        };
+        if !wallet.accounts.iter().any(|a| a.id == account_id) {
+            return Ok(false)
+        }
+        self.update_meta(|m| {
+            let wallet_meta = m.wallets.entry(wallet_id).or_insert_with(WalletMeta::default);
+            let mut account_meta = wallet_meta.get_account(account_id);
+            patch.apply(&mut account_meta);
+            wallet_meta.set_account(account_id, account_meta);
+            wallet_meta.updated_at = Some(now_millis());
+        })?;
+        Ok(true)
+    }
+
+    /// Moves an account with its key reference and details to another wallet, returns the new id of the account.
+    /// Both wallets and the metadata are written while the metadata is locked, and if any write fails the previous
+    /// ones are reverted. If reverting fails too, the error tells in which state the account is left
+    fn move_account(&self, wallet_id: Uuid, account_id: usize, target_id: Uuid) -> Result<AccountId, VaultError> {
+        if wallet_id == target_id {
+            return Err(VaultError::InvalidDataError("Same wallet".to_string()))
+        }
+        let storage = &self.cfg.get_storage();
+        let original_source = storage.wallets().get(&wallet_id)?;
+        let mut wallet = original_source.clone();
+        let original_target = storage.wallets().get(&target_id)?;
+        let index = wallet.accounts.iter().position(|a| a.id == account_id)
+            .ok_or(VaultError::InvalidDataError("Account not found".to_string()))?;
+        let mut account = wallet.accounts.remove(index);
+        let exists = original_target.accounts.iter()
+            .any(|a| a.blockchain == account.blockchain && a.address.is_some() && a.address == account.address);
+        if exists {
+            return Err(VaultError::InvalidDataError("Account already exists in the target wallet".to_string()))
+        }
+
+        let _lock = self.lock_meta()?;
+        let mut meta = self.read_meta()?;
+        let mut target = original_target.clone();
+        account.id = next_account_id(&target, &meta.get_wallet(&target_id));
+        let new_id = account.id;
+        target.accounts.push(account);
+        let moved = AccountId::from(&target, target.accounts.last().unwrap());
+
+        let now = now_millis();
+        let account_meta = {
+            let source_meta = meta.wallets.entry(wallet_id).or_insert_with(WalletMeta::default);
+            source_meta.updated_at = Some(now);
+            source_meta.next_account_id = source_meta.next_account_id.max(account_id + 1);
+            source_meta.accounts.remove(&account_id)
+        };
+        let target_meta = meta.wallets.entry(target_id).or_insert_with(WalletMeta::default);
+        target_meta.updated_at = Some(now);
+        target_meta.next_account_id = new_id + 1;
+        target_meta.set_account(new_id, account_meta.unwrap_or_default());
+
+        storage.wallets().update(target)?;
+        if let Err(e) = storage.wallets().update(wallet) {
+            return match storage.wallets().update(original_target) {
+                Ok(_) => Err(e),
+                Err(_) => Err(VaultError::InvalidDataError(format!(
+                    "Account {} is copied to wallet {} as {}, but not removed from wallet {}",
+                    account_id, target_id, new_id, wallet_id
+                )))
+            }
+        }
+        if let Err(e) = self.save_meta(&meta) {
+            let restored = storage.wallets().update(original_source)
+                .and_then(|_| storage.wallets().update(original_target));
+            return match restored {
+                Ok(_) => Err(e),
+                Err(_) => Err(VaultError::InvalidDataError(format!(
+                    "Account {} is moved to wallet {} as {}, but its details are left in wallet {}",
+                    account_id, target_id, new_id, wallet_id
+                )))
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Creates a new wallet with accounts of all the specified wallets, and removes the original wallets. Accounts
+    /// keep their keys, an account with the same address on the same blockchain is added only once, with a key if
+    /// any of the duplicates has it. Only the account of a dropped duplicate is removed, its private key stays in the
+    /// vault because it may be encrypted with another password. The new wallet takes the label, if it's not provided,
+    /// and the details of the first wallet which has them. Returns id of the new wallet
+    fn merge_wallets(&self, wallet_ids: Vec<Uuid>, label: Option<String>) -> Result<Uuid, VaultError> {
+        let storage = &self.cfg.get_storage();
+        let mut sources: Vec<Wallet> = Vec::new();
+        for id in wallet_ids {
+            if !sources.iter().any(|w| w.id == id) {
+                sources.push(storage.wallets().get(&id)?);
+            }
+        }
+        if sources.is_empty() {
+            return Err(VaultError::InvalidDataError("No wallets to merge".to_string()))
+        }
+
+        let meta = self.read_meta()?;
+        let now = now_millis();
+        let label = label.and_then(|s| none_if_empty(s.as_str()))
+            .or_else(|| sources.iter().find_map(|w| w.label.clone()));
+        let mut merged = Wallet {
+            id: Uuid::new_v4(),
+            label,
+            accounts: vec![]
+        };
+        let source_meta: Vec<WalletMeta> = sources.iter().map(|w| meta.get_wallet(&w.id)).collect();
+        let mut merged_meta = WalletMeta {
+            description: source_meta.iter().find_map(|m| m.description.clone()),
+            color: source_meta.iter().find_map(|m| m.color.clone()),
+            icon: source_meta.iter().find_map(|m| m.icon.clone()),
+            order: source_meta.iter().find_map(|m| m.order),
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..WalletMeta::default()
+        };
+        for (wallet, wallet_meta) in sources.iter().zip(source_meta.iter()) {
+            for account in &wallet.accounts {
+                let existing = merged.accounts.iter()
+                    .position(|a| a.blockchain == account.blockchain && a.address.is_some() && a.address == account.address);
+                match existing {
+                    Some(i) => {
+                        // keep a key if any of the duplicates has it
+                        if is_watch_only(&merged.accounts[i]) && !is_watch_only(account) {
+                            merged.accounts[i].key = account.key.clone();
+                        }
+                    },
+                    None => {
+                        let mut copy = account.clone();
+                        copy.id = next_account_id(&merged, &merged_meta);
+                        merged_meta.set_account(copy.id, wallet_meta.get_account(account.id));
+                        merged.accounts.push(copy);
+                    }
+                }
+            }
+        }
+
+        merged_meta.next_account_id = next_account_id(&merged, &merged_meta);
+        let merged_id = merged.id;
+        storage.wallets().add(merged)?;
+        for (i, wallet) in sources.iter().enumerate() {
+            if let Err(e) = storage.wallets().remove(&wallet.id) {
+                // put back already removed wallets, so the accounts are not duplicated and not lost
+                for removed in &sources[0..i] {
+                    storage.wallets().add(removed.clone())?;
+                }
+                storage.wallets().remove(&merged_id)?;
+                return Err(e)
+            }
+        }
+        self.update_meta(|m| {
+            for wallet in &sources {
+                m.wallets.remove(&wallet.id);
+            }
+            m.wallets.insert(merged_id, merged_meta);
+        })?;
+        Ok(merged_id)
+    }
+
+    /// Moves the specified accounts to a new wallet, returns id of the new wallet. All accounts must be in the
+    /// same wallet
+    fn split_wallet(&self, account_ids: Vec<(Uuid, usize)>, label: Option<String>) -> Result<Uuid, VaultError> {
+        let wallet_id = match account_ids.first() {
+            Some((wallet_id, _)) => *wallet_id,
+            None => return Err(VaultError::InvalidDataError("No accounts to split".to_string()))
+        };
+        if account_ids.iter().any(|(id, _)| *id != wallet_id) {
+            return Err(VaultError::InvalidDataError("Accounts belong to different wallets".to_string()))
+        }
+        let account_ids: Vec<usize> = account_ids.into_iter().map(|(_, id)| id).collect();
+        let storage = &self.cfg.get_storage();
+        let mut wallet = storage.wallets().get(&wallet_id)?;
+        if account_ids.iter().any(|id| !wallet.accounts.iter().any(|a| a.id == *id)) {
+            return Err(VaultError::InvalidDataError("Account not found".to_string()))
+        }
+
+        let wallet_meta = self.read_meta()?.get_wallet(&wallet_id);
+        let now = now_millis();
+        let (moved, kept): (Vec<WalletAccount>, Vec<WalletAccount>) = wallet.accounts.into_iter()
+            .partition(|a| account_ids.contains(&a.id));
+        let mut split = Wallet {
+            id: Uuid::new_v4(),
+            label: label.and_then(|s| none_if_empty(s.as_str())),
+            accounts: vec![]
+        };
+        let mut split_meta = WalletMeta {
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..WalletMeta::default()
+        };
+        for mut account in moved {
+            let original_id = account.id;
+            account.id = next_account_id(&split, &split_meta);
+            split_meta.set_account(account.id, wallet_meta.get_account(original_id));
+            split.accounts.push(account);
+        }
+        split_meta.next_account_id = next_account_id(&split, &split_meta);
+        wallet.accounts = kept;
+
+        let split_id = split.id;
+        storage.wallets().add(split)?;
+        if let Err(e) = storage.wallets().update(wallet) {
+            storage.wallets().remove(&split_id)?;
+            return Err(e)
+        }
+        self.update_meta(|m| {
+            let source_meta = m.wallets.entry(wallet_id).or_insert_with(WalletMeta::default);
+            source_meta.accounts.retain(|id, _| !account_ids.contains(id));
+            source_meta.updated_at = Some(now);
+            m.wallets.insert(split_id, split_meta);
+        })?;
+        Ok(split_id)
     }
 
     fn create_account(&self, wallet_id: Uuid, account: AddAccountJson) -> Result<usize, VaultError> {
@@ -107,9 +619,8 @@ impl WrappedVault {
         let result = match account.key_value {
             AddAccountType::EthereumJson(json) => {
                 let json = EthereumJsonV3File::try_from(json)?;
-                let id = storage.add_account(wallet_id)
-                    .ethereum(&json, blockchain)?;
-                id
+                storage.add_account(wallet_id)
+                    .ethereum(&json, blockchain)?
             },
             AddAccountType::RawHex(hex) => {
                 if account.password.is_none() {
@@ -121,12 +632,17 @@ impl WrappedVault {
                     .raw_pk(hex, account.password.unwrap().as_str(), blockchain)?
             },
             AddAccountType::HdPath(hd) => {
-                storage.add_account(wallet_id)
-                    .seed_hd(Uuid::from_str(hd.seed_id.as_str())?,
-                             HDPath::try_from(hd.hd_path.as_str())?,
-                             blockchain,
-                             Some(hd.password),
-                             None)?
+                let seed_id = Uuid::from_str(hd.seed_id.as_str())?;
+                if self.is_ledger_seed(seed_id)? {
+                    self.add_ledger_account(wallet_id, blockchain, seed_id, hd)?
+                } else {
+                    storage.add_account(wallet_id)
+                        .seed_hd(seed_id,
+                                 HDPath::try_from(hd.hd_path.as_str())?,
+                                 blockchain,
+                                 hd.password,
+                                 None)?
+                }
             },
             AddAccountType::GenerateRandom => {
                 if account.password.is_none() {
@@ -135,11 +651,74 @@ impl WrappedVault {
                 let pk = PrivateKey::gen();
                 storage.add_account(wallet_id)
                     .raw_pk(pk.0.to_vec(), account.password.unwrap().as_str(), blockchain)?
+            },
+            AddAccountType::WatchOnly(watch) => {
+                let addresses = watch.list_addresses()?;
+                self.add_watch_only(wallet_id, blockchain, addresses)?
             }
         };
+        self.touch_wallet(wallet_id)?;
         Ok(result)
     }
 
+    /// Adds an account for each of the addresses, returns id of the first one. The accounts have the
+    /// `watch_only_key`
+    fn add_watch_only(&self, wallet_id: Uuid, blockchain: Blockchain, addresses: Vec<Address>) -> Result<usize, VaultError> {
+        let storage = &self.cfg.get_storage();
+        let _lock = self.lock_meta()?;
+        let mut meta = self.read_meta()?;
+        let mut wallet = storage.wallets().get(&wallet_id)?;
+        let first_id = next_account_id(&wallet, &meta.get_wallet(&wallet_id));
+        let mut next_id = first_id;
+        for (i, address) in addresses.into_iter().enumerate() {
+            let id = first_id.checked_add(i)
+                .ok_or_else(|| VaultError::InvalidDataError("Too many accounts".to_string()))?;
+            wallet.accounts.push(WalletAccount {
+                id,
+                blockchain,
+                address: Some(address),
+                key: watch_only_key()
+            });
+            next_id = id + 1;
+        }
+        storage.wallets().update(wallet)?;
+        meta.wallets.entry(wallet_id).or_insert_with(WalletMeta::default).next_account_id = next_id;
+        self.save_meta(&meta)?;
+        Ok(first_id)
+    }
+
+    /// Adds an account on a Hardware Wallet seed, the address is taken from the device and must match the expected one
+    /// if it's provided
+    fn add_ledger_account(&self, wallet_id: Uuid, blockchain: Blockchain, seed_id: Uuid, hd: SeedAccount) -> Result<usize, VaultError> {
+        let storage = &self.cfg.get_storage();
+        let hd_path = HDPath::try_from(hd.hd_path.as_str())?;
+        let address = self.get_hw_key_for_seed(&seed_id)?.get_address(&parse_hd_path(hd.hd_path.as_str())?)?;
+        if let Some(expected) = hd.address {
+            let expected = Address::from_str(expected.as_str())
+                .map_err(|_| VaultError::InvalidDataError("address".to_string()))?;
+            if expected != address {
+                return Err(VaultError::InvalidDataError("Address doesn't match the connected Hardware Wallet".to_string()))
+            }
+        }
+        let _lock = self.lock_meta()?;
+        let mut meta = self.read_meta()?;
+        let mut wallet = storage.wallets().get(&wallet_id)?;
+        let id = next_account_id(&wallet, &meta.get_wallet(&wallet_id));
+        wallet.accounts.push(WalletAccount {
+            id,
+            blockchain,
+            address: Some(address),
+            key: PKType::SeedHd(SeedRef {
+                seed_id,
+                hd_path
+            })
+        });
+        storage.wallets().update(wallet)?;
+        meta.wallets.entry(wallet_id).or_insert_with(WalletMeta::default).next_account_id = id + 1;
+        self.save_meta(&meta)?;
+        Ok(id)
+    }
+
     pub fn update(&self, wallet: Wallet) -> Result<(), VaultError> {
         let storage = &self.cfg.get_storage();
         storage.wallets().update(wallet)?;
@@ -151,7 +730,7 @@ impl WrappedVault {
         let mut wallet = storage.wallets().get(&wallet_id)?;
         wallet.label = title;
         storage.wallets().update(wallet)?;
-        Ok(())
+        self.touch_wallet(wallet_id)
     }
 
     fn remove_account(&self, wallet_id: Uuid, account_id: usize) -> Result<bool, VaultError> {
@@ -162,28 +741,31 @@ impl WrappedVault {
             return Ok(false)
         }
         wallet.accounts.remove(index.unwrap());
-        storage.wallets().update(wallet)
+        let updated = storage.wallets().update(wallet)?;
+        self.update_meta(|m| {
+            let wallet_meta = m.wallets.entry(wallet_id).or_insert_with(WalletMeta::default);
+            wallet_meta.accounts.remove(&account_id);
+            // the id may be the last one, and must not be given to the next account
+            wallet_meta.next_account_id = wallet_meta.next_account_id.max(account_id + 1);
+            wallet_meta.updated_at = Some(now_millis());
+        })?;
+        Ok(updated)
     }
 }
 
 pub fn list(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
-    let wallets = vault.load_wallets();
+    let result = vault.list_wallets();
 
-    let mut result = Vec::new();
-    for w in wallets {
-        result.push(WalletJson::from(w));
-    }
-
-    let status = StatusResult::Ok(result).as_json();
+    let status = StatusResult::from(result).as_json();
 
     let js_value = neon_serde::to_value(&mut cx, &status)?;
     Ok(js_value.downcast().unwrap())
 }
 
 pub fn add(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let label = cx.argument::<JsString>(1)
@@ -197,7 +779,7 @@ pub fn add(mut cx: FunctionContext) -> JsResult<JsObject> {
 }
 
 pub fn add_account_to_wallet(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let wallet_id =read_wallet_id(&mut cx, 1);
@@ -213,7 +795,7 @@ pub fn add_account_to_wallet(mut cx: FunctionContext) -> JsResult<JsObject> {
 }
 
 pub fn update_label(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let wallet_id =read_wallet_id(&mut cx, 1);
@@ -225,8 +807,85 @@ pub fn update_label(mut cx: FunctionContext) -> JsResult<JsObject> {
     Ok(js_value.downcast().unwrap())
 }
 
+pub fn update(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let wallet_id = read_wallet_id(&mut cx, 1);
+    let json = cx.argument::<JsString>(2).expect("Update JSON is not provided").value();
+    let patch: WalletPatch = serde_json::from_str(json.as_str()).expect("Invalid JSON");
+
+    let result = vault.update_wallet(wallet_id, patch);
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status)?;
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn update_account(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let (wallet_id, account_id) = read_wallet_and_account_ids(&mut cx, 1);
+    let json = cx.argument::<JsString>(3).expect("Update JSON is not provided").value();
+    let patch: AccountPatch = serde_json::from_str(json.as_str()).expect("Invalid JSON");
+
+    let result = vault.update_account(wallet_id, account_id, patch);
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status)?;
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn move_account(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let (wallet_id, account_id) = read_wallet_and_account_ids(&mut cx, 1);
+    let target_id = read_wallet_id(&mut cx, 3);
+
+    let result = vault.move_account(wallet_id, account_id, target_id)
+        .map(|id| id.to_string());
+    // keep the message, it tells where the account is if the move failed halfway
+    let status = match result {
+        Err(VaultError::InvalidDataError(msg)) => StatusResult::Error(status_code::VAULT_ERROR, msg),
+        other => StatusResult::from(other)
+    }.as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status)?;
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn merge(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let wallet_ids = read_wallet_ids(&mut cx, 1);
+    let label = args_get_str(&mut cx, 2);
+
+    let result = vault.merge_wallets(wallet_ids, label)
+        .map(|id| id.to_string());
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status)?;
+    Ok(js_value.downcast().unwrap())
+}
+
+pub fn split(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let cfg = VaultConfig::get_config(&mut cx)?;
+    let vault = WrappedVault::new(cfg);
+
+    let account_ids = read_account_ids(&mut cx, 1);
+    let label = args_get_str(&mut cx, 2);
+
+    let result = account_ids.iter()
+        .map(|id| parse_account_id(id.as_str()))
+        .collect::<Result<Vec<(Uuid, usize)>, VaultError>>()
+        .and_then(|ids| vault.split_wallet(ids, label))
+        .map(|id| id.to_string());
+    let status = StatusResult::from(result).as_json();
+    let js_value = neon_serde::to_value(&mut cx, &status)?;
+    Ok(js_value.downcast().unwrap())
+}
+
 pub fn remove_account(mut cx: FunctionContext) -> JsResult<JsObject> {
-    let cfg = VaultConfig::get_config(&mut cx);
+    let cfg = VaultConfig::get_config(&mut cx)?;
     let vault = WrappedVault::new(cfg);
 
     let (wallet_id, account_id) = read_wallet_and_account_ids(&mut cx, 1);
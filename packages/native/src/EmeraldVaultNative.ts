@@ -1,7 +1,14 @@
-import {Config, Status, StatusCode,} from './types';
+import {Config, Status, StatusCode, StatusError} from './types';
 import {
     AddAccount,
     AddressBookItem,
+    AddressBookItemUpdate,
+    AddressBookAddResult,
+    AddressBookFormat,
+    AddressBookImportMode,
+    AddressBookImportResult,
+    AddressBookFilter,
+    AddressBookPage,
     BlockchainType,
     isLedger,
     isMnemonic,
@@ -9,9 +16,19 @@ import {
     isReference,
     SeedDefinition,
     SeedDescription,
+    SeedReference,
+    SeedRemoveResult,
+    SeedExport,
     UnsignedTx,
     Uuid,
     Wallet,
+    WalletUpdate,
+    WalletAccountUpdate,
+    HDPathAddress,
+    LedgerDevice,
+    LedgerApp,
+    LedgerAddressConfirmation,
+    LedgerSeed,
     IEmeraldVault, AccountId, AccountIdOp, WalletsOp
 } from "@emeraldpay/emerald-vault-core";
 
@@ -27,6 +44,10 @@ function statusFail<T>(code: StatusCode = StatusCode.UNKNOWN, message: string =
     }
 }
 
+function statusError<T>(status: Status<T>): StatusError {
+    return Object.assign(Error(status.error.message), {code: status.error.code});
+}
+
 function statusOk<T>(result: T): Status<T> {
     return {
         succeeded: true,
@@ -83,10 +104,34 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return status.result
     }
 
+    updateWallet(walletId: Uuid, update: WalletUpdate): boolean {
+        let status: Status<boolean> = addon.wallets_update(this.conf, walletId, JSON.stringify(update));
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
     removeWallet(walletId: Uuid) {
         throw Error("NOT IMPLEMENTED");
     }
 
+    mergeWallets(walletIds: Uuid[], label?: string): Uuid {
+        let status: Status<Uuid> = addon.wallets_merge(this.conf, walletIds, label);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    splitWallet(accountIds: AccountId[], label?: string): Uuid {
+        let status: Status<Uuid> = addon.wallets_split(this.conf, accountIds, label);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
     addAccount(walletId: Uuid, account: AddAccount): AccountId {
         let status: Status<number> = addon.wallets_addAccount(this.conf, walletId, JSON.stringify(account));
         if (!status.succeeded) {
@@ -95,6 +140,24 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return AccountIdOp.create(walletId, status.result).value
     }
 
+    updateAccount(accountFullId: AccountId, update: WalletAccountUpdate): boolean {
+        let op = AccountIdOp.of(accountFullId);
+        let status: Status<boolean> = addon.wallets_updateAccount(this.conf, op.extractWalletId(), op.extractAccountInternalId(), JSON.stringify(update));
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    moveAccount(accountFullId: AccountId, targetWalletId: Uuid): AccountId {
+        let op = AccountIdOp.of(accountFullId);
+        let status: Status<AccountId> = addon.wallets_moveAccount(this.conf, op.extractWalletId(), op.extractAccountInternalId(), targetWalletId);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
     removeAccount(accountFullId: AccountId) {
         let op = AccountIdOp.of(accountFullId);
         let status: Status<boolean> = addon.wallets_removeAccount(this.conf, op.extractWalletId(), op.extractAccountInternalId());
@@ -108,7 +171,7 @@ export class EmeraldVaultNative implements IEmeraldVault {
         let op = AccountIdOp.of(accountFullId);
         let status: Status<string> = addon.sign_tx(this.conf, op.extractWalletId(), op.extractAccountInternalId(), JSON.stringify(tx), password);
         if (!status.succeeded) {
-            throw Error(status.error.message)
+            throw statusError(status)
         }
         return "0x" + status.result;
     }
@@ -140,27 +203,75 @@ export class EmeraldVaultNative implements IEmeraldVault {
     }
 
     listAddressBook(blockchain: number): AddressBookItem[] {
+        return this.queryAddressBook({blockchain}).items;
+    }
+
+    queryAddressBook(filter: AddressBookFilter): AddressBookPage {
+        let opts = Object.assign({}, this.conf);
+        let status: Status<AddressBookPage> = addon.addrbook_list(opts, JSON.stringify(filter));
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    addToAddressBook(item: AddressBookItem): AddressBookAddResult {
+        let opts = Object.assign({}, this.conf);
+        let status: Status<AddressBookAddResult> = addon.addrbook_add(opts, JSON.stringify(item));
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    removeFromAddressBook(blockchain: number, address: string): number {
+        let opts = Object.assign({}, this.conf);
+        let status: Status<number> = addon.addrbook_remove(opts, blockchain, address);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    removeAddressBookItem(id: Uuid): number {
         let opts = Object.assign({}, this.conf);
-        let status: Status<AddressBookItem[]> = addon.addrbook_list(opts);
+        let status: Status<number> = addon.addrbook_removeById(opts, id);
         if (!status.succeeded) {
             throw Error(status.error.message)
         }
         return status.result
-            .filter((item) => item.blockchain == blockchain);
     }
 
-    addToAddressBook(item: AddressBookItem): boolean {
+    findAddressBookByName(name: string): AddressBookItem[] {
         let opts = Object.assign({}, this.conf);
-        let status: Status<boolean> = addon.addrbook_add(opts, JSON.stringify(item));
+        let status: Status<AddressBookItem[]> = addon.addrbook_findByName(opts, name);
         if (!status.succeeded) {
             throw Error(status.error.message)
         }
         return status.result
     }
 
-    removeFromAddressBook(blockchain: number, address: string): boolean {
+    updateAddressBookItem(id: Uuid, update: AddressBookItemUpdate): boolean {
         let opts = Object.assign({}, this.conf);
-        let status: Status<boolean> = addon.addrbook_remove(opts, address);
+        let status: Status<boolean> = addon.addrbook_update(opts, id, JSON.stringify(update));
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    exportAddressBook(format: AddressBookFormat): string {
+        let opts = Object.assign({}, this.conf);
+        let status: Status<string> = addon.addrbook_export(opts, format);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    importAddressBook(data: string, format: AddressBookFormat, mode?: AddressBookImportMode): AddressBookImportResult {
+        let opts = Object.assign({}, this.conf);
+        let status: Status<AddressBookImportResult> = addon.addrbook_import(opts, data, format, mode || "merge");
         if (!status.succeeded) {
             throw Error(status.error.message)
         }
@@ -175,8 +286,80 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return status.result;
     }
 
+    setSeedLabel(seedId: Uuid, label: string): boolean {
+        let status: Status<boolean> = addon.seed_updateLabel(this.conf, seedId, label);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    removeSeed(seedId: Uuid, password: string, force?: boolean): SeedRemoveResult {
+        let status: Status<SeedRemoveResult> = addon.seed_remove(this.conf, seedId, password, force === true);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    exportSeed(seedId: Uuid, password: string): SeedExport {
+        let status: Status<SeedExport> = addon.seed_export(this.conf, seedId, password);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    getXpub(seedId: Uuid, password: string, hdPath: string): string {
+        let status: Status<string> = addon.seed_getXpub(this.conf, seedId, password, hdPath);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    scanSeedAddresses(seedId: Uuid, password: string, basePath: string, startIndex: number, count: number): HDPathAddress[] {
+        let status: Status<HDPathAddress[]> = addon.seed_scanAddresses(this.conf, seedId, password, basePath, startIndex, count);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
     getConnectedHWSeed(create: boolean): SeedDescription | undefined {
-        return undefined
+        let current = this.listSeeds().find((seed) => seed.type === "ledger" && seed.available);
+        if (typeof current !== "undefined" || !create) {
+            return current;
+        }
+        if (!addon.ledger_isConnected(this.conf)) {
+            return undefined;
+        }
+        let id = this.importSeed({type: "ledger", value: {}});
+        return this.listSeeds().find((seed) => seed.id === id);
+    }
+
+    listLedgerDevices(): LedgerDevice[] {
+        let status: Status<LedgerDevice[]> = addon.ledger_listDevices(this.conf);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    getLedgerApp(device?: string): LedgerApp {
+        let status: Status<LedgerApp> = addon.ledger_getApp(this.conf, device);
+        if (!status.succeeded) {
+            throw Error(status.error.message)
+        }
+        return status.result
+    }
+
+    confirmLedgerAddress(hdPath: string, device?: string): LedgerAddressConfirmation {
+        let status: Status<LedgerAddressConfirmation> = addon.ledger_confirmAddress(this.conf, hdPath, device);
+        if (!status.succeeded) {
+            throw statusError(status)
+        }
+        return status.result
     }
 
     importSeed(seed: SeedDefinition): Uuid {
@@ -189,7 +372,8 @@ export class EmeraldVaultNative implements IEmeraldVault {
 
     isSeedAvailable(seed: Uuid | SeedDefinition): boolean {
         if (isReference(seed)) {
-            return addon.ledger_isConnected(seed);
+            let current = this.listSeeds().find((it) => it.id === seed);
+            return typeof current !== "undefined" && current.available;
         } else {
             if (isRawSeed(seed.value, seed)) {
                 return seed.value.length > 0;
@@ -198,13 +382,24 @@ export class EmeraldVaultNative implements IEmeraldVault {
                 return seed.value.value.length > 0;
             }
             if (isLedger(seed.value, seed)) {
-                return addon.ledger_isConnected(seed);
+                return addon.ledger_isConnected(this.conf, seed.value.device);
             }
         }
         return false;
     }
 
-    listSeedAddresses(seed: Uuid | SeedDefinition, blockchain: BlockchainType, hdpath: string[]): { [key: string]: string } {
-        return addon.ledger_listAddresses(JSON.stringify(seed), blockchain, hdpath);
+    listSeedAddresses(seed: Uuid | SeedDefinition | SeedReference, blockchain: BlockchainType, hdpath: string[]): { [key: string]: string } {
+        if (isReference(seed)) {
+            seed = {type: "seed-ref", value: seed};
+        }
+        let device = undefined;
+        if (seed.type === "ledger" && typeof seed.value === "object") {
+            device = (seed.value as LedgerSeed).device;
+        }
+        let status: Status<{ [key: string]: string }> = addon.ledger_listAddresses(this.conf, JSON.stringify(seed), blockchain, hdpath, device);
+        if (!status.succeeded) {
+            throw statusError(status)
+        }
+        return status.result
     }
 }
\ No newline at end of file
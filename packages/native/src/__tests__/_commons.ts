@@ -1,7 +1,69 @@
+import {EmeraldVaultNative} from "../EmeraldVaultNative";
+import {Uuid} from "@emeraldpay/emerald-vault-core";
+
 var seq = 0;
 
 export function tempPath(prefix: string) {
     const ts = new Date().getTime() - 1576037200000;
     seq++;
     return `./testdata/tmp-${prefix}-${ts}-${seq}`;
-}
\ No newline at end of file
+}
+
+/**
+ * Mnemonic of the seed used by tests, its first addresses on m/44'/60'/0'/0/x are
+ * 0x110c84fCC6A775f788d3CA6A9492Abd5B3fEc588 and 0xb4BbAaC4Acd7E86AF282e80C7a62fda78D071950
+ */
+export const TEST_MNEMONIC = "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea";
+
+/**
+ * Imports the test mnemonic into the vault, encrypted with the password
+ */
+export function importTestSeed(vault: EmeraldVaultNative, password: string = "test"): Uuid {
+    return vault.importSeed({
+        type: "mnemonic",
+        value: {
+            value: TEST_MNEMONIC
+        },
+        password
+    });
+}
+
+/**
+ * Transaction from the first address of the emulated Hardware Wallets
+ */
+export const HW_TX = {
+    from: "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+    to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+    value: "0x1051",
+    gas: "0x5208",
+    gasPrice: "0x77359400",
+    nonce: "0x2"
+};
+
+/**
+ * Adds a wallet with the first account of the connected Hardware Wallet, returns the account id
+ */
+export function createHWAccount(vault: EmeraldVaultNative, label: string): string {
+    let seed = vault.getConnectedHWSeed(true);
+    let walletId = vault.addWallet(label);
+    return vault.addAccount(walletId, {
+        blockchain: 100,
+        type: "hd-path",
+        key: {
+            seedId: seed.id,
+            hdPath: "m/44'/60'/0'/0/0"
+        }
+    });
+}
+
+/**
+ * Error thrown when signing HW_TX, or undefined if it's signed
+ */
+export function signError(vault: EmeraldVaultNative, accountId: string): any {
+    try {
+        vault.signTx(accountId, HW_TX);
+    } catch (e) {
+        return e;
+    }
+    return undefined;
+}
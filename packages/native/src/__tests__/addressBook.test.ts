@@ -30,6 +30,64 @@ describe("Address Book", () => {
             expect(accounts.length).toBe(0);
         });
 
+        test("search by name", () => {
+            let page = vault.queryAddressBook({query: "NAME"});
+            expect(page.total).toBe(1);
+            expect(page.items[0].name).toBe("name 1");
+        });
+
+        test("search by description", () => {
+            let page = vault.queryAddressBook({blockchain: 100, query: "тест"});
+            expect(page.total).toBe(1);
+            expect(page.items[0].address).toBe("0xb3c9a2f3f96ffbc4b7ded2d92c83175698147ae2");
+        });
+
+        test("search by address", () => {
+            let page = vault.queryAddressBook({query: "C2D7CF9"});
+            expect(page.total).toBe(1);
+            expect(page.items[0].address).toBe("0xc2d7cf95645d33006175b78989035c7c9061d3f9");
+        });
+
+    });
+
+    describe('Pagination', () => {
+
+        let vault;
+        beforeAll(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("book-page")
+            });
+            for (let i = 0; i < 5; i++) {
+                vault.addToAddressBook({name: "eth " + i, address: "0xc2d7cf95645d33006175b78989035c7c9061d3f" + i, blockchain: 100});
+            }
+            vault.addToAddressBook({name: "etc", address: "0xc2d7cf95645d33006175b78989035c7c9061d3fa", blockchain: 101});
+        });
+
+        test("all items without filter", () => {
+            let page = vault.queryAddressBook({});
+            expect(page.total).toBe(6);
+            expect(page.items.length).toBe(6);
+        });
+
+        test("limit and offset", () => {
+            let first = vault.queryAddressBook({blockchain: 100, limit: 2});
+            expect(first.total).toBe(5);
+            expect(first.items.length).toBe(2);
+
+            let last = vault.queryAddressBook({blockchain: 100, offset: 4, limit: 2});
+            expect(last.total).toBe(5);
+            expect(last.items.length).toBe(1);
+
+            let all = vault.queryAddressBook({blockchain: 100}).items.map((b) => b.id);
+            expect(first.items.map((b) => b.id)).toEqual(all.slice(0, 2));
+            expect(last.items[0].id).toBe(all[4]);
+        });
+
+        test("offset after the end", () => {
+            let page = vault.queryAddressBook({blockchain: 101, offset: 5});
+            expect(page.total).toBe(1);
+            expect(page.items.length).toBe(0);
+        });
     });
 
     describe('Test empty book', () => {
@@ -71,6 +129,434 @@ describe("Address Book", () => {
             expect(accounts[0].address).toBe("0xc2d7cf95645d33006175b78989035c7c9061d3f9".toLowerCase());
             expect(accounts[0].name).toBe("test 1");
             expect(accounts[0].description).toBeNull();
+            expect(accounts[0].id).toMatch(/^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/);
+        });
+    });
+
+    describe('Add Duplicates', () => {
+
+        let vault;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("book-duplicate")
+            });
+        });
+
+        test("created", () => {
+            let act = vault.addToAddressBook({name: "test 1", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            expect(act.result).toBe("created");
+            expect(act.id).toBe(vault.listAddressBook(100)[0].id);
+        });
+
+        test("reject duplicate", () => {
+            let first = vault.addToAddressBook({name: "test 1", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let act = vault.addToAddressBook({name: "test 2", address: "0xC2D7CF95645D33006175B78989035C7C9061D3F9", blockchain: 100});
+            expect(act.result).toBe("rejected");
+            expect(act.reason).toBe("duplicate");
+            expect(act.id).toBe(first.id);
+
+            let items = vault.listAddressBook(100);
+            expect(items.length).toBe(1);
+            expect(items[0].name).toBe("test 1");
+        });
+
+        test("merge missing details", () => {
+            let first = vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let act = vault.addToAddressBook({name: "test 2", description: "desc", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            expect(act.result).toBe("merged");
+            expect(act.id).toBe(first.id);
+
+            let items = vault.listAddressBook(100);
+            expect(items.length).toBe(1);
+            expect(items[0].id).toBe(first.id);
+            expect(items[0].name).toBe("test 2");
+            expect(items[0].description).toBe("desc");
+        });
+
+        test("same address on different blockchains", () => {
+            vault.addToAddressBook({name: "eth", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let act = vault.addToAddressBook({name: "etc", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 101});
+            expect(act.result).toBe("created");
+        });
+
+        test("accept valid checksum", () => {
+            let act = vault.addToAddressBook({address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 100});
+            expect(act.result).toBe("created");
+        });
+
+        test("reject invalid checksum", () => {
+            let act = vault.addToAddressBook({address: "0xb3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 100});
+            expect(act.result).toBe("rejected");
+            expect(act.reason).toBe("invalid-checksum");
+            expect(vault.listAddressBook(100).length).toBe(0);
+        });
+
+        test("reject invalid address", () => {
+            let act = vault.addToAddressBook({address: "0xb3c9a2f3", blockchain: 100});
+            expect(act.result).toBe("rejected");
+            expect(act.reason).toBe("invalid-address");
+        });
+    });
+
+    describe('Tags and Metadata', () => {
+
+        let vault;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("book-tags")
+            });
+        });
+
+        test("empty by default", () => {
+            vault.addToAddressBook({name: "test 1", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let act = vault.listAddressBook(100)[0];
+            expect(act.tags).toEqual([]);
+            expect(act.metadata).toEqual({});
+        });
+
+        test("add with tags and metadata", () => {
+            vault.addToAddressBook({
+                name: "exchange", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100,
+                tags: ["exchange", " hot ", "", "Exchange"],
+                metadata: {account: "12345", memo: "deposit"}
+            });
+            let act = vault.listAddressBook(100)[0];
+            expect(act.tags).toEqual(["exchange", "hot"]);
+            expect(act.metadata).toEqual({account: "12345", memo: "deposit"});
+        });
+
+        test("filter by tag", () => {
+            vault.addToAddressBook({name: "exchange", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, tags: ["exchange"]});
+            vault.addToAddressBook({name: "vendor", address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 100, tags: ["vendors"]});
+            vault.addToAddressBook({name: "vendor etc", address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 101, tags: ["Vendors"]});
+
+            let act = vault.queryAddressBook({tag: "vendors"});
+            expect(act.total).toBe(2);
+            expect(act.items.map((b) => b.name).sort()).toEqual(["vendor", "vendor etc"]);
+
+            act = vault.queryAddressBook({tag: "VENDORS", blockchain: 100});
+            expect(act.total).toBe(1);
+            expect(act.items[0].name).toBe("vendor");
+
+            expect(vault.queryAddressBook({tag: "payroll"}).total).toBe(0);
+        });
+
+        test("reject invalid filter", () => {
+            expect(() => {
+                vault.queryAddressBook({blockchain: "eth"});
+            }).toThrow("Invalid filter JSON");
+        });
+
+        test("merge tags of duplicate", () => {
+            vault.addToAddressBook({name: "exchange", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, tags: ["exchange"], metadata: {a: "1"}});
+            let act = vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, tags: ["hot"], metadata: {a: "2", b: "3"}});
+            expect(act.result).toBe("merged");
+            let item = vault.listAddressBook(100)[0];
+            expect(item.name).toBe("exchange");
+            expect(item.tags).toEqual(["exchange", "hot"]);
+            expect(item.metadata).toEqual({a: "1", b: "3"});
+
+            act = vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, tags: ["HOT"]});
+            expect(act.result).toBe("rejected");
+        });
+
+        test("update tags and metadata", () => {
+            vault.addToAddressBook({name: "exchange", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, tags: ["exchange"], metadata: {a: "1"}});
+            let id = vault.listAddressBook(100)[0].id;
+
+            vault.updateAddressBookItem(id, {tags: ["payroll"]});
+            let item = vault.listAddressBook(100)[0];
+            expect(item.tags).toEqual(["payroll"]);
+            expect(item.metadata).toEqual({a: "1"});
+
+            vault.updateAddressBookItem(id, {metadata: {}});
+            item = vault.listAddressBook(100)[0];
+            expect(item.tags).toEqual(["payroll"]);
+            expect(item.metadata).toEqual({});
+        });
+
+        test("removed with the item", () => {
+            vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, tags: ["exchange"]});
+            vault.removeFromAddressBook(100, "0xc2d7cf95645d33006175b78989035c7c9061d3f9");
+            vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            expect(vault.listAddressBook(100)[0].tags).toEqual([]);
+        });
+    });
+
+    describe('ENS Names', () => {
+
+        let vault;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("book-ens")
+            });
+        });
+
+        test("add with name", () => {
+            let act = vault.addToAddressBook({
+                address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100,
+                ens: {name: "Alice.eth", resolvedAt: 1588000000000}
+            });
+            expect(act.result).toBe("created");
+            let item = vault.listAddressBook(100)[0];
+            expect(item.ens).toEqual({name: "alice.eth", resolvedAt: 1588000000000});
+        });
+
+        test("resolved now if time is not set", () => {
+            let start = Date.now();
+            vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, ens: {name: "alice.eth"}});
+            let item = vault.listAddressBook(100)[0];
+            expect(item.ens.resolvedAt).toBeGreaterThanOrEqual(start);
+            expect(item.ens.resolvedAt).toBeLessThanOrEqual(Date.now());
+        });
+
+        test("no name by default", () => {
+            vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            expect(vault.listAddressBook(100)[0].ens).toBeNull();
+        });
+
+        test("reject invalid name", () => {
+            let act = vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, ens: {name: "alice"}});
+            expect(act.result).toBe("rejected");
+            expect(act.reason).toBe("invalid-ens-name");
+        });
+
+        test("find by name", () => {
+            vault.addToAddressBook({name: "eth", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100, ens: {name: "alice.eth"}});
+            vault.addToAddressBook({name: "etc", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 101, ens: {name: "alice.eth"}});
+            vault.addToAddressBook({name: "bob", address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 100, ens: {name: "bob.eth"}});
+
+            let act = vault.findAddressBookByName(" ALICE.eth");
+            expect(act.map((b) => b.name).sort()).toEqual(["etc", "eth"]);
+            expect(vault.findAddressBookByName("carol.eth")).toEqual([]);
+        });
+
+        test("update and clear name", () => {
+            vault.addToAddressBook({address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let id = vault.listAddressBook(100)[0].id;
+
+            vault.updateAddressBookItem(id, {ens: {name: "alice.eth", resolvedAt: 1588000000000}});
+            expect(vault.findAddressBookByName("alice.eth").length).toBe(1);
+
+            vault.updateAddressBookItem(id, {ens: null});
+            expect(vault.findAddressBookByName("alice.eth").length).toBe(0);
+            expect(vault.listAddressBook(100)[0].ens).toBeNull();
+        });
+    });
+
+    describe('Export and Import', () => {
+
+        let vault;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("book-import")
+            });
+        });
+
+        test("export csv", () => {
+            vault.addToAddressBook({name: "test, 1", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let act = vault.exportAddressBook("csv").split("\n");
+            expect(act[0]).toBe("blockchain,address,name,description,tags,metadata,ens,ensResolvedAt");
+            expect(act[1]).toBe("100,0xc2d7cf95645d33006175b78989035c7c9061d3f9,\"test, 1\",,,,,");
+        });
+
+        test("export csv with details", () => {
+            vault.addToAddressBook({
+                name: "test", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100,
+                tags: ["a", "b"], metadata: {key: "value"}, ens: {name: "alice.eth", resolvedAt: 1600000000000}
+            });
+            let act = vault.exportAddressBook("csv").split("\n");
+            expect(act[1]).toBe("100,0xc2d7cf95645d33006175b78989035c7c9061d3f9,test,,a;b,\"{\"\"key\"\":\"\"value\"\"}\",alice.eth,1600000000000");
+        });
+
+        test("export json", () => {
+            vault.addToAddressBook({name: "test 1", description: "desc", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 101});
+            let act = JSON.parse(vault.exportAddressBook("json"));
+            expect(act).toEqual([
+                {
+                    blockchain: 101, address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", name: "test 1", description: "desc",
+                    tags: [], metadata: {}, ens: null
+                }
+            ]);
+        });
+
+        test("export json with details", () => {
+            vault.addToAddressBook({
+                address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100,
+                tags: ["friend"], metadata: {key: "value"}, ens: {name: "alice.eth", resolvedAt: 1600000000000}
+            });
+            let act = JSON.parse(vault.exportAddressBook("json"));
+            expect(act[0].tags).toEqual(["friend"]);
+            expect(act[0].metadata).toEqual({key: "value"});
+            expect(act[0].ens).toEqual({name: "alice.eth", resolvedAt: 1600000000000});
+        });
+
+        test("reject unknown format", () => {
+            expect(() => {
+                vault.exportAddressBook("xml");
+            }).toThrow("Invalid format: xml");
+            expect(() => {
+                vault.importAddressBook("[]", "xml");
+            }).toThrow("Invalid format: xml");
+        });
+
+        test("reject unknown import mode", () => {
+            expect(() => {
+                vault.importAddressBook("[]", "json", "append");
+            }).toThrow("Invalid import mode: append");
+        });
+
+        test("import csv with report", () => {
+            vault.addToAddressBook({name: "existing", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let data = "blockchain,address,name,description\n" +
+                "100,0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2,first,\n" +
+                "100,0xc2d7cf95645d33006175b78989035c7c9061d3f9,duplicate,\n" +
+                "100,0xb3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2,bad checksum,\n" +
+                "eth,0xc2d7cf95645d33006175b78989035c7c9061d3f9,invalid,\n" +
+                "101,0xc2d7cf95645d33006175b78989035c7c9061d3f9,etc,\"with, comma\"\n";
+            let act = vault.importAddressBook(data, "csv", "merge");
+            expect(act.created).toBe(2);
+            expect(act.merged).toBe(0);
+            expect(act.rejected).toBe(3);
+            expect(act.removed).toBe(0);
+            expect(act.rows.map((r) => r.result)).toEqual(["created", "rejected", "rejected", "rejected", "created"]);
+            expect(act.rows.map((r) => r.reason)).toEqual([null, "duplicate", "invalid-checksum", "invalid-row", null]);
+            expect(act.rows[4].row).toBe(5);
+
+            expect(vault.listAddressBook(100).length).toBe(2);
+            let etc = vault.listAddressBook(101);
+            expect(etc.length).toBe(1);
+            expect(etc[0].description).toBe("with, comma");
+        });
+
+        test("import json with replace", () => {
+            vault.addToAddressBook({name: "old", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let data = JSON.stringify([
+                {blockchain: 100, address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", name: "new"},
+                {blockchain: 12345, address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9"},
+                {address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9"},
+            ]);
+            let act = vault.importAddressBook(data, "json", "replace");
+            expect(act.removed).toBe(1);
+            expect(act.created).toBe(1);
+            expect(act.rows.map((r) => r.reason)).toEqual([null, "invalid-blockchain", "invalid-row"]);
+
+            let items = vault.listAddressBook(100);
+            expect(items.length).toBe(1);
+            expect(items[0].name).toBe("new");
+        });
+
+        test("invalid data keeps existing items", () => {
+            vault.addToAddressBook({name: "old", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            expect(() => {
+                vault.importAddressBook("not a json", "json", "replace");
+            }).toThrow();
+            expect(vault.listAddressBook(100).length).toBe(1);
+        });
+
+        test("export and import back", () => {
+            vault.addToAddressBook({name: "first", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            vault.addToAddressBook({name: "second", description: "desc", address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 101});
+            let exported = vault.exportAddressBook("csv");
+
+            let target = new EmeraldVaultNative({dir: tempPath("book-import")});
+            let act = target.importAddressBook(exported, "csv");
+            expect(act.created).toBe(2);
+            const sorted = (json) => JSON.parse(json).sort((a, b) => a.address.localeCompare(b.address));
+            expect(sorted(target.exportAddressBook("json"))).toEqual(sorted(vault.exportAddressBook("json")));
+        });
+
+        test("import back with details", () => {
+            vault.addToAddressBook({
+                name: "first", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100,
+                tags: ["a", "b"], metadata: {key: "value"}, ens: {name: "alice.eth", resolvedAt: 1600000000000}
+            });
+            vault.addToAddressBook({name: "second", address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 101, tags: ["c"]});
+
+            ["csv", "json"].forEach((format) => {
+                let exported = vault.exportAddressBook(format);
+                let target = new EmeraldVaultNative({dir: tempPath("book-import")});
+                let act = target.importAddressBook(exported, format, "replace");
+                expect(act.created).toBe(2);
+
+                let first = target.listAddressBook(100)[0];
+                expect(first.tags).toEqual(["a", "b"]);
+                expect(first.metadata).toEqual({key: "value"});
+                expect(first.ens).toEqual({name: "alice.eth", resolvedAt: 1600000000000});
+                expect(target.listAddressBook(101)[0].tags).toEqual(["c"]);
+            });
+        });
+    });
+
+    describe('Update Item', () => {
+
+        let vault;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("book-update")
+            });
+        });
+
+        test("change name and keep id", () => {
+            vault.addToAddressBook({name: "test 1", description: "desc", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let id = vault.listAddressBook(100)[0].id;
+
+            let updated = vault.updateAddressBookItem(id, {name: "test 2"});
+            expect(updated).toBeTruthy();
+
+            let accounts = vault.listAddressBook(100);
+            expect(accounts.length).toBe(1);
+            expect(accounts[0].id).toBe(id);
+            expect(accounts[0].name).toBe("test 2");
+            expect(accounts[0].description).toBe("desc");
+            expect(accounts[0].address).toBe("0xc2d7cf95645d33006175b78989035c7c9061d3f9");
+        });
+
+        test("change address and clear description", () => {
+            vault.addToAddressBook({name: "test 1", description: "desc", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let id = vault.listAddressBook(100)[0].id;
+
+            vault.updateAddressBookItem(id, {description: null, address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2"});
+
+            let accounts = vault.listAddressBook(100);
+            expect(accounts[0].id).toBe(id);
+            expect(accounts[0].name).toBe("test 1");
+            expect(accounts[0].description).toBeNull();
+            expect(accounts[0].address).toBe("0xb3c9a2f3f96ffbc4b7ded2d92c83175698147ae2");
+        });
+
+        test("reject invalid checksum and keep item", () => {
+            vault.addToAddressBook({name: "test 1", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let id = vault.listAddressBook(100)[0].id;
+
+            expect(() => {
+                vault.updateAddressBookItem(id, {name: "test 2", address: "0xb3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2"});
+            }).toThrow("invalid-checksum");
+
+            let accounts = vault.listAddressBook(100);
+            expect(accounts.length).toBe(1);
+            expect(accounts[0].id).toBe(id);
+            expect(accounts[0].name).toBe("test 1");
+            expect(accounts[0].address).toBe("0xc2d7cf95645d33006175b78989035c7c9061d3f9");
+        });
+
+        test("reject address of another item", () => {
+            vault.addToAddressBook({name: "test 1", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            vault.addToAddressBook({name: "test 2", address: "0xb3c9a2f3f96ffbc4b7ded2d92c83175698147ae2", blockchain: 100});
+            let id = vault.listAddressBook(100).find((it) => it.name == "test 1").id;
+
+            expect(() => {
+                vault.updateAddressBookItem(id, {address: "0xb3c9a2f3f96ffbc4b7ded2d92c83175698147ae2"});
+            }).toThrow("duplicate");
+
+            let accounts = vault.listAddressBook(100);
+            expect(accounts.length).toBe(2);
+            expect(accounts.find((it) => it.id == id).address).toBe("0xc2d7cf95645d33006175b78989035c7c9061d3f9");
+        });
+
+        test("unknown id", () => {
+            let updated = vault.updateAddressBookItem("9d4cba3c-2f34-4b2c-8bd5-3d3bd4d0e8c4", {name: "test"});
+            expect(updated).toBeFalsy();
         });
     });
 
@@ -91,9 +577,39 @@ describe("Address Book", () => {
             expect(accounts.length).toBe(1);
             expect(accounts[0].address).toBe("0xc2d7cf95645d33006175b78989035c7c9061d3f9".toLowerCase());
 
-            vault.removeFromAddressBook("etc", "0xc2d7cf95645d33006175b78989035c7c9061d3f9".toLowerCase());
+            let removed = vault.removeFromAddressBook(101, "0xc2d7cf95645d33006175b78989035c7c9061d3f9".toLowerCase());
+            expect(removed).toBe(1);
             accounts = vault.listAddressBook(101);
             expect(accounts.length).toBe(0);
         });
+
+        test("delete only on the specified blockchain", () => {
+            vault.addToAddressBook({name: "eth", address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 100});
+            vault.addToAddressBook({name: "etc", address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 101});
+
+            let removed = vault.removeFromAddressBook(101, "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2");
+            expect(removed).toBe(1);
+            expect(vault.listAddressBook(101).length).toBe(0);
+            let eth = vault.listAddressBook(100);
+            expect(eth.length).toBe(1);
+            expect(eth[0].name).toBe("eth");
+
+            expect(vault.removeFromAddressBook(101, "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2")).toBe(0);
+            vault.removeFromAddressBook(100, "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2");
+        });
+
+        test("delete by id", () => {
+            vault.addToAddressBook({name: "first", address: "0xB3c9A2f3F96ffBC4b7DEd2D92C83175698147Ae2", blockchain: 100});
+            vault.addToAddressBook({name: "second", address: "0xc2d7cf95645d33006175b78989035c7c9061d3f9", blockchain: 100});
+            let items = vault.listAddressBook(100);
+            expect(items.length).toBe(2);
+
+            expect(vault.removeAddressBookItem(items[0].id)).toBe(1);
+            let left = vault.listAddressBook(100);
+            expect(left.length).toBe(1);
+            expect(left[0].id).toBe(items[1].id);
+
+            expect(vault.removeAddressBookItem(items[0].id)).toBe(0);
+        });
     });
 });
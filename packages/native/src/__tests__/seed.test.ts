@@ -1,6 +1,7 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
-import {tempPath} from "./_commons";
-import {AddAccount, EthereumAccount, SeedDefinition, WalletsOp, WalletOp} from "@emeraldpay/emerald-vault-core";
+import {tempPath, importTestSeed, TEST_MNEMONIC} from "./_commons";
+import {StatusCode} from "../types";
+import {AddAccount, EthereumAccount, SeedDefinition, SeedPKRef, WalletsOp, WalletOp, HDPathTemplate} from "@emeraldpay/emerald-vault-core";
 
 const should_exist = process.env.EMERALD_TEST_LEDGER === 'true';
 
@@ -46,6 +47,18 @@ describe("Seeds", () => {
                 expect(act["m/44'/60'/0'/0/2"]).toBe(process.env.EMERALD_TEST_LEDGER_P2.toLowerCase());
             });
 
+            test("Error when not connected", () => {
+                if (should_exist) {
+                    return;
+                }
+                try {
+                    vault.listSeedAddresses(type, "ethereum", ["m/44'/60'/0'/0/0"]);
+                    fail("Must throw");
+                } catch (e) {
+                    expect([StatusCode.HW_NOT_CONNECTED, StatusCode.HW_ERROR]).toContain(e.code);
+                }
+            });
+
         });
     });
 
@@ -160,6 +173,46 @@ describe("Seeds", () => {
         });
     });
 
+    describe("List addresses of stored seed", () => {
+        let vault: EmeraldVaultNative;
+        beforeAll(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-ref")
+            });
+        });
+
+        test("List ethereum", () => {
+            let id = importTestSeed(vault);
+            const act = vault.listSeedAddresses({type: "seed-ref", value: id, password: "test"}, "ethereum", [
+                "m/44'/60'/0'/0/0",
+                "m/44'/60'/0'/0/1",
+            ]);
+            expect(act["m/44'/60'/0'/0/0"]).toBe('0x110c84fCC6A775f788d3CA6A9492Abd5B3fEc588'.toLowerCase());
+            expect(act["m/44'/60'/0'/0/1"]).toBe('0xb4BbAaC4Acd7E86AF282e80C7a62fda78D071950'.toLowerCase());
+        });
+
+        test("Error without password", () => {
+            let id = importTestSeed(vault);
+            try {
+                vault.listSeedAddresses({type: "seed-ref", value: id}, "ethereum", ["m/44'/60'/0'/0/0"]);
+                fail("Must throw");
+            } catch (e) {
+                expect(e.code).toBe(StatusCode.VAULT_ERROR);
+            }
+        });
+
+        test("Error with only id", () => {
+            let id = importTestSeed(vault);
+            try {
+                vault.listSeedAddresses(id, "ethereum", ["m/44'/60'/0'/0/0"]);
+                fail("Must throw");
+            } catch (e) {
+                expect(e.code).toBe(StatusCode.VAULT_ERROR);
+                expect(e.message).toBe("Password is required");
+            }
+        });
+    });
+
     describe("Create Seed", () => {
         let vault: EmeraldVaultNative;
         beforeEach(() => {
@@ -185,7 +238,189 @@ describe("Seeds", () => {
 
             let seeds = vault.listSeeds();
             expect(seeds.length).toBe(1);
-        })
+            expect(seeds[0].type).toBe("raw");
+        });
+
+        test("Update label", () => {
+            let id = importTestSeed(vault);
+
+            let seeds = vault.listSeeds();
+            expect(seeds[0].label).toBeNull();
+            expect(seeds[0].createdAt).toBeGreaterThan(0);
+
+            vault.setSeedLabel(id, "My Seed");
+            seeds = vault.listSeeds();
+            expect(seeds[0].label).toBe("My Seed");
+
+            vault.setSeedLabel(id, "");
+            seeds = vault.listSeeds();
+            expect(seeds[0].label).toBeNull();
+        });
+    });
+
+    describe("Extended public key", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-xpub")
+            });
+        });
+
+        test("Get account xpub", () => {
+            let id = importTestSeed(vault);
+            let xpub = vault.getXpub(id, "test", "m/44'/60'/0'");
+            expect(xpub).toBe("xpub6BkyEFUnmCvXJdkApVfSA6vPBzrZnXH4foF54bGRM3gggQ4MYzaebpjmM2aTmcEg2mMR8RJmkMtGjThcxSQzvk3wtWzn766rm6VRVHSm2vb");
+        });
+
+        test("Fails with wrong password", () => {
+            let id = importTestSeed(vault);
+            expect(() => vault.getXpub(id, "wrong", "m/44'/60'/0'")).toThrow();
+        });
+
+        test("Same addresses as the seed", () => {
+            let id = importTestSeed(vault);
+            let xpub = vault.getXpub(id, "test", "m/44'/60'/1'");
+            let walletId = vault.addWallet("watch");
+            vault.addAccount(walletId, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {xpub, start: 0, count: 3}
+            });
+            let fromXpub = WalletsOp.of(vault.listWallets()).getWallet(walletId).getEthereumAccounts().map((a) => a.address);
+
+            const act = vault.listSeedAddresses({type: "seed-ref", value: id, password: "test"}, "ethereum", [
+                "m/44'/60'/1'/0/0",
+                "m/44'/60'/1'/0/1",
+                "m/44'/60'/1'/0/2",
+            ]);
+            expect(fromXpub).toEqual([
+                act["m/44'/60'/1'/0/0"],
+                act["m/44'/60'/1'/0/1"],
+                act["m/44'/60'/1'/0/2"],
+            ]);
+        });
+    });
+
+    describe("Scan addresses", () => {
+        let vault: EmeraldVaultNative;
+        let seedId: string;
+        beforeAll(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-scan")
+            });
+            seedId = importTestSeed(vault);
+        });
+
+        test("Scan standard path", () => {
+            let act = vault.scanSeedAddresses(seedId, "test", HDPathTemplate.BIP44_ETHEREUM, 0, 2);
+            expect(act.length).toBe(2);
+            expect(act[0]).toEqual({hdPath: "m/44'/60'/0'/0/0", address: "0x110c84fcc6a775f788d3ca6a9492abd5b3fec588"});
+            expect(act[1]).toEqual({hdPath: "m/44'/60'/0'/0/1", address: "0xb4bbaac4acd7e86af282e80c7a62fda78d071950"});
+        });
+
+        test("Scan Ledger Live path", () => {
+            let act = vault.scanSeedAddresses(seedId, "test", HDPathTemplate.LEDGER_LIVE, 1, 1);
+            expect(act).toEqual([{hdPath: "m/44'/60'/1'/0/0", address: "0x2eace5def8d8387e480c6490ffd70aea04da31a9"}]);
+        });
+
+        test("Scan legacy path", () => {
+            let act = vault.scanSeedAddresses(seedId, "test", "m/44'/60'/0'", 0, 2);
+            expect(act[0]).toEqual({hdPath: "m/44'/60'/0'/0", address: "0x65723e1de30a8d84f0867acd91badeffb590c23f"});
+            expect(act[1]).toEqual({hdPath: "m/44'/60'/0'/1", address: "0x41f443f7546d7858f39b79cb82416b72a6706a02"});
+        });
+
+        test("Scan ETC path", () => {
+            let act = vault.scanSeedAddresses(seedId, "test", HDPathTemplate.BIP44_ETHEREUM_CLASSIC, 0, 2);
+            expect(act[0].address).toBe("0xb137fc49524cdf6698ef53a0d8813098656b3cb3");
+            expect(act[1].address).toBe("0x6370588691c0f4fac970a9e59251f7840b4e2952");
+        });
+    });
+
+    describe("Export Seed", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-export")
+            });
+        });
+
+        test("Export mnemonic", () => {
+            let id = importTestSeed(vault);
+            let act = vault.exportSeed(id, "test");
+            expect(act).toEqual({
+                type: "mnemonic",
+                value: TEST_MNEMONIC,
+                passphrase: false
+            });
+            // the phrase is not listed as a separate seed
+            expect(vault.listSeeds().length).toBe(1);
+        });
+
+        test("Export mnemonic with passphrase", () => {
+            let id = vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: TEST_MNEMONIC,
+                    password: "passphrase"
+                },
+                password: "test"
+            });
+            let act = vault.exportSeed(id, "test");
+            expect(act.type).toBe("mnemonic");
+            expect(act.value).toBe(TEST_MNEMONIC);
+            expect(act.passphrase).toBeTruthy();
+        });
+
+        test("Fails with wrong password", () => {
+            let id = importTestSeed(vault);
+            expect(() => vault.exportSeed(id, "wrong")).toThrow();
+        });
+    });
+
+    describe("Remove Seed", () => {
+        let vault: EmeraldVaultNative;
+        let seedId: string;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-remove")
+            });
+            seedId = importTestSeed(vault);
+        });
+
+        test("Remove unused", () => {
+            let result = vault.removeSeed(seedId, "test");
+            expect(result.removed).toBeTruthy();
+            expect(result.accounts).toEqual([]);
+            expect(vault.listSeeds().length).toBe(0);
+        });
+
+        test("Requires password", () => {
+            expect(() => vault.removeSeed(seedId, "wrong")).toThrow();
+            expect(vault.listSeeds().length).toBe(1);
+        });
+
+        test("Keep used seed unless forced", () => {
+            let walletId = vault.addWallet("test seed");
+            let accId = vault.addAccount(walletId, {
+                blockchain: 100,
+                type: "hd-path",
+                key: {
+                    hdPath: "m/44'/60'/0'/0/1",
+                    seedId: seedId,
+                    password: "test"
+                }
+            });
+
+            let result = vault.removeSeed(seedId, "test");
+            expect(result.removed).toBeFalsy();
+            expect(result.accounts).toEqual([accId]);
+            expect(vault.listSeeds().length).toBe(1);
+
+            result = vault.removeSeed(seedId, "test", true);
+            expect(result.removed).toBeTruthy();
+            expect(result.accounts).toEqual([accId]);
+            expect(vault.listSeeds().length).toBe(0);
+        });
     });
 
     describe("Create Account", () => {
@@ -225,8 +460,257 @@ describe("Seeds", () => {
             let account = wallet.accounts[0] as EthereumAccount;
             expect(account.address).toBe("0xb4BbAaC4Acd7E86AF282e80C7a62fda78D071950".toLowerCase());
 
-            // let key = wallet.accounts[0].key as SeedPKRef;
-            // expect(key.hdPath).toBe("m/44'/60'/0'/0/1");
+            let seeds = vault.listSeeds();
+            expect(seeds[0].accounts).toEqual([accId]);
+
+            let key = wallet.accounts[0].key as SeedPKRef;
+            expect(key.type).toBe("seed-hd");
+            expect(key.seedId).toBe(id);
+            expect(key.hdPath).toBe("m/44'/60'/0'/0/1");
         })
     });
-});
\ No newline at end of file
+
+    describe("Emulated Ledger", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-emulator"),
+                hardware: "emulator"
+            });
+        });
+
+        test("Connected", () => {
+            expect(vault.isSeedAvailable({type: "ledger", value: {}})).toBeTruthy();
+        });
+
+        test("Fails on invalid hd path", () => {
+            expect(() => {
+                vault.listSeedAddresses({type: "ledger", value: {}}, "ethereum", ["m/44'/60'/x"]);
+            }).toThrow();
+        });
+
+        test("List addresses", () => {
+            const act = vault.listSeedAddresses({type: "ledger", value: {}}, "ethereum", [
+                "m/44'/60'/0'/0/0",
+                "m/44'/60'/0'/0/1",
+                "m/44'/61'/0'/0/0",
+            ]);
+            expect(act["m/44'/60'/0'/0/0"]).toBe("0x9858effd232b4033e47d90003d41ec34ecaeda94");
+            expect(act["m/44'/60'/0'/0/1"]).toBe("0x6fac4d18c912343bf86fa7049364dd4e424ab9c0");
+            expect(act["m/44'/61'/0'/0/0"]).toBe("0xfa22515e43658ce56a7682b801e9b5456f511420");
+        });
+
+        test("Register connected seed", () => {
+            expect(vault.getConnectedHWSeed(false)).toBeUndefined();
+
+            let seed = vault.getConnectedHWSeed(true);
+            expect(seed).toBeDefined();
+            expect(seed.type).toBe("ledger");
+            expect(seed.available).toBeTruthy();
+            expect(vault.isSeedAvailable(seed.id)).toBeTruthy();
+
+            // doesn't create a second seed for the same device
+            let again = vault.getConnectedHWSeed(true);
+            expect(again.id).toBe(seed.id);
+            expect(vault.listSeeds().length).toBe(1);
+
+            const act = vault.listSeedAddresses(seed.id, "ethereum", ["m/44'/60'/0'/0/2"]);
+            expect(act["m/44'/60'/0'/0/2"]).toBe("0xb6716976a3ebe8d39aceb04372f22ff8e6802d7a");
+        });
+
+        test("Create account", () => {
+            let seed = vault.getConnectedHWSeed(true);
+            let walletId = vault.addWallet("ledger");
+            vault.addAccount(walletId, {
+                blockchain: 100,
+                type: "hd-path",
+                key: {
+                    hdPath: "m/44'/60'/0'/0/1",
+                    seedId: seed.id,
+                    address: "0x6fac4d18c912343bf86fa7049364dd4e424ab9c0"
+                }
+            });
+            let wallet = WalletsOp.of(vault.listWallets()).getWallet(walletId).value;
+            expect(wallet.accounts.length).toBe(1);
+            let account = wallet.accounts[0] as EthereumAccount;
+            expect(account.address).toBe("0x6fac4d18c912343bf86fa7049364dd4e424ab9c0");
+
+            let key = account.key as SeedPKRef;
+            expect(key.type).toBe("ledger");
+            expect(key.seedId).toBe(seed.id);
+            expect(key.hdPath).toBe("m/44'/60'/0'/0/1");
+        });
+
+        test("Reject account with different address", () => {
+            let seed = vault.getConnectedHWSeed(true);
+            let walletId = vault.addWallet("ledger");
+            expect(() => {
+                vault.addAccount(walletId, {
+                    blockchain: 100,
+                    type: "hd-path",
+                    key: {
+                        hdPath: "m/44'/60'/0'/0/1",
+                        seedId: seed.id,
+                        address: "0x9858effd232b4033e47d90003d41ec34ecaeda94"
+                    }
+                });
+            }).toThrow();
+        });
+    });
+
+    describe("Multiple Ledgers", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-emulators"),
+                hardware: "emulator,emulator,emulator:locked"
+            });
+        });
+
+        test("List devices", () => {
+            const act = vault.listLedgerDevices();
+            expect(act.length).toBe(3);
+            expect(act[0]).toEqual({id: "emulator:0", vendor: "ledger", appVersion: "1.4.0"});
+            expect(act[1]).toEqual({id: "emulator:1", vendor: "ledger", appVersion: "1.4.0"});
+            expect(act[2].id).toBe("emulator:2");
+            expect(act[2].appVersion).toBeNull();
+        });
+
+        test("List addresses on selected device", () => {
+            const first = vault.listSeedAddresses({type: "ledger", value: {}}, "ethereum", ["m/44'/60'/0'/0/0"]);
+            expect(first["m/44'/60'/0'/0/0"]).toBe("0x9858effd232b4033e47d90003d41ec34ecaeda94");
+
+            const second = vault.listSeedAddresses({type: "ledger", value: {device: "emulator:1"}}, "ethereum", ["m/44'/60'/0'/0/0"]);
+            expect(second["m/44'/60'/0'/0/0"]).toBe("0x58a57ed9d8d624cbd12e2c467d34787555bb1b25");
+        });
+
+        test("Unknown device is not connected", () => {
+            expect(vault.isSeedAvailable({type: "ledger", value: {device: "emulator:1"}})).toBeTruthy();
+            expect(vault.isSeedAvailable({type: "ledger", value: {device: "emulator:5"}})).toBeFalsy();
+        });
+
+        test("Use device with the same seed", () => {
+            let id = vault.importSeed({type: "ledger", value: {device: "emulator:1"}});
+            const act = vault.listSeedAddresses(id, "ethereum", ["m/44'/60'/0'/0/1"]);
+            expect(act["m/44'/60'/0'/0/1"]).toBe("0x0d3eb21b6b21833a4939cfff4810e9ae0758e12c");
+            expect(vault.isSeedAvailable(id)).toBeTruthy();
+        });
+    });
+
+    describe("Emulated Trezor", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-trezor"),
+                hardware: "trezor-emulator"
+            });
+        });
+
+        test("List devices", () => {
+            const act = vault.listLedgerDevices();
+            expect(act).toEqual([{id: "trezor-emulator:0", vendor: "trezor", appVersion: "1.9.0"}]);
+        });
+
+        test("List addresses", () => {
+            const act = vault.listSeedAddresses({type: "ledger", value: {}}, "ethereum", [
+                "m/44'/60'/0'/0/0",
+                "m/44'/60'/0'/0/1",
+                "m/44'/61'/0'/0/0",
+            ]);
+            expect(act["m/44'/60'/0'/0/0"]).toBe("0x9858effd232b4033e47d90003d41ec34ecaeda94");
+            expect(act["m/44'/60'/0'/0/1"]).toBe("0x6fac4d18c912343bf86fa7049364dd4e424ab9c0");
+            expect(act["m/44'/61'/0'/0/0"]).toBe("0xfa22515e43658ce56a7682b801e9b5456f511420");
+        });
+
+        test("Register connected seed", () => {
+            let seed = vault.getConnectedHWSeed(true);
+            expect(seed).toBeDefined();
+            expect(seed.available).toBeTruthy();
+            const act = vault.listSeedAddresses(seed.id, "ethereum", ["m/44'/60'/0'/0/2"]);
+            expect(act["m/44'/60'/0'/0/2"]).toBe("0xb6716976a3ebe8d39aceb04372f22ff8e6802d7a");
+        });
+
+        test("Select between Ledger and Trezor", () => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-trezor"),
+                hardware: "emulator,trezor-emulator"
+            });
+            const devices = vault.listLedgerDevices();
+            expect(devices.map((d) => d.vendor)).toEqual(["ledger", "trezor"]);
+
+            let id = vault.importSeed({type: "ledger", value: {device: "trezor-emulator:1"}});
+            const act = vault.listSeedAddresses(id, "ethereum", ["m/44'/60'/0'/0/1"]);
+            expect(act["m/44'/60'/0'/0/1"]).toBe("0x0d3eb21b6b21833a4939cfff4810e9ae0758e12c");
+        });
+
+        test("App state", () => {
+            expect(vault.getLedgerApp()).toEqual({
+                connected: true, locked: false, app: "Trezor", version: "1.9.0", ethereum: true
+            });
+        });
+
+        test("Locked", () => {
+            vault = new EmeraldVaultNative({dir: tempPath("seed-trezor"), hardware: "trezor-emulator:locked"});
+            let act = vault.getLedgerApp();
+            expect(act.locked).toBeTruthy();
+            expect(act.ethereum).toBeFalsy();
+        });
+
+        test("Confirm address", () => {
+            let act = vault.confirmLedgerAddress("m/44'/60'/0'/0/1");
+            expect(act).toEqual({address: "0x6fac4d18c912343bf86fa7049364dd4e424ab9c0", confirmed: true});
+        });
+
+        test("Address rejected by user", () => {
+            vault = new EmeraldVaultNative({dir: tempPath("seed-trezor"), hardware: "trezor-emulator:reject"});
+            let act = vault.confirmLedgerAddress("m/44'/60'/0'/0/1");
+            expect(act.confirmed).toBeFalsy();
+        });
+    });
+
+    describe("Ledger App", () => {
+
+        test("Ethereum App opened", () => {
+            let vault = new EmeraldVaultNative({dir: tempPath("seed-app"), hardware: "emulator"});
+            expect(vault.getLedgerApp()).toEqual({
+                connected: true, locked: false, app: "Ethereum", version: "1.4.0", ethereum: true
+            });
+        });
+
+        test("On dashboard", () => {
+            let vault = new EmeraldVaultNative({dir: tempPath("seed-app"), hardware: "emulator:no-app"});
+            let act = vault.getLedgerApp();
+            expect(act.connected).toBeTruthy();
+            expect(act.app).toBe("BOLOS");
+            expect(act.ethereum).toBeFalsy();
+        });
+
+        test("Locked", () => {
+            let vault = new EmeraldVaultNative({dir: tempPath("seed-app"), hardware: "emulator:locked"});
+            let act = vault.getLedgerApp();
+            expect(act.connected).toBeTruthy();
+            expect(act.locked).toBeTruthy();
+            expect(act.ethereum).toBeFalsy();
+        });
+
+        test("Not connected", () => {
+            let vault = new EmeraldVaultNative({dir: tempPath("seed-app"), hardware: "emulator"});
+            let act = vault.getLedgerApp("emulator:3");
+            expect(act.connected).toBeFalsy();
+            expect(act.ethereum).toBeFalsy();
+        });
+
+        test("Confirm address", () => {
+            let vault = new EmeraldVaultNative({dir: tempPath("seed-app"), hardware: "emulator"});
+            let act = vault.confirmLedgerAddress("m/44'/60'/0'/0/1");
+            expect(act).toEqual({address: "0x6fac4d18c912343bf86fa7049364dd4e424ab9c0", confirmed: true});
+        });
+
+        test("Address rejected by user", () => {
+            let vault = new EmeraldVaultNative({dir: tempPath("seed-app"), hardware: "emulator:reject"});
+            let act = vault.confirmLedgerAddress("m/44'/60'/0'/0/1");
+            expect(act.confirmed).toBeFalsy();
+            expect(act.address).toBeNull();
+        });
+    });
+});
@@ -1,6 +1,7 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
-import {tempPath} from "./_commons";
+import {tempPath, HW_TX, createHWAccount, signError} from "./_commons";
 import {WalletsOp, WalletOp, AccountIdOp} from "@emeraldpay/emerald-vault-core";
+import {StatusCode} from "../types";
 
 
 describe("Sign transaction", () => {
@@ -202,7 +203,74 @@ describe("Sign transaction", () => {
             expect(raw).toBe("0xf863198477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3808025a02eab8b290050239e77329cb6d0d663c9bdbf0fe15918e4937be727dd67a0c593a05dda8f7b748b5907c0b414be260809f9c2dcfcd35a4a9b1cc801a7f4fe2154eb");
         });
     });
-});
 
+    describe.each([
+        ["ledger", "emulator"],
+        ["trezor", "trezor-emulator"]
+    ])('Use emulated %s', (device, hardware) => {
+        let dir: string;
+        let accountId: string;
+        beforeEach(() => {
+            dir = tempPath("sign-" + device);
+            accountId = createHWAccount(new EmeraldVaultNative({dir, hardware}), device);
+        });
+
+        test("sign without password", () => {
+            let vault = new EmeraldVaultNative({dir, hardware});
+            let raw = vault.signTx(accountId, HW_TX);
+            expect(raw).toBe("0xf865028477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38210518026a034821b6d586c1ee9ae4266559fbbae353e735c1bd28ce6ed2284d39b989ec866a07e6fae6c41969271a79155ed562abd99047d661d2fe70cef17b8099e3c3d0b4a");
+        });
+
+        test("fails when locked", () => {
+            let vault = new EmeraldVaultNative({dir, hardware: hardware + ":locked"});
+            expect(signError(vault, accountId).code).toBe(StatusCode.HW_LOCKED);
+        });
+
+        test("fails when rejected by user", () => {
+            let vault = new EmeraldVaultNative({dir, hardware: hardware + ":reject"});
+            expect(signError(vault, accountId).code).toBe(StatusCode.HW_REJECTED);
+        });
+
+        if (device == "ledger") {
+            test("sign large data in multiple chunks", () => {
+                let vault = new EmeraldVaultNative({dir, hardware});
+                let transfer = "a9059cbb0000000000000000000000000d0707963952f2fba59dd06f2b425ace40b492fe0000000000000000000000000000000000000000000000000000000000000000";
+                let raw = vault.signTx(accountId, {
+                    from: "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+                    to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                    value: "0x0",
+                    gas: "0x5208",
+                    gasPrice: "0x77359400",
+                    nonce: "0x19",
+                    data: "0x" + transfer + transfer + transfer
+                });
+                expect(raw).toBe("0xf90130198477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd380b8cc" + transfer + transfer + transfer +
+                    "25a0dc60f28dcbf228dd8f4669c5b99ae44e07c74a448e1f732180a4398d2cd8c920a06ab9ec6cad8aec67d45d5e7ac165bd9255f3141f1292435fc8afac8907f91285");
+            });
 
+            test("fails when app is not opened", () => {
+                let vault = new EmeraldVaultNative({dir, hardware: hardware + ":no-app"});
+                expect(signError(vault, accountId).code).toBe(StatusCode.HW_WRONG_APP);
+            });
+        }
 
+        if (device == "trezor") {
+            test("sign data requested by device in chunks", () => {
+                let vault = new EmeraldVaultNative({dir, hardware});
+                let transfer = "a9059cbb0000000000000000000000000d0707963952f2fba59dd06f2b425ace40b492fe0000000000000000000000000000000000000000000000000000000000000000";
+                let data = transfer.repeat(16);
+                let raw = vault.signTx(accountId, {
+                    from: "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+                    to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                    value: "0x0",
+                    gas: "0x30d40",
+                    gasPrice: "0x77359400",
+                    nonce: "0x1a",
+                    data: "0x" + data
+                });
+                expect(raw).toBe("0xf904a61a847735940083030d40943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd380b90440" + data +
+                    "25a086891ae3afc0fc8a6c9739f88bbfe440b2635129122fb529bc4f8c82ee857322a07510f224d2758689b381c6c3ac87810f38dbbefddb5203a78b720af04ece87d7");
+            });
+        }
+    });
+});
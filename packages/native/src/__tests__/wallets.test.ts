@@ -1,6 +1,7 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
 import {AddAccount, WalletOp, WalletsOp, AccountIdOp} from "@emeraldpay/emerald-vault-core";
 import {tempPath} from "./_commons";
+import * as fs from "fs";
 
 describe("Wallets", () => {
 
@@ -114,6 +115,13 @@ describe("Wallets", () => {
                 let wallet = WalletsOp.of(vault.listWallets()).getWallet(id);
                 expect(wallet.value.accounts.length).toBe(1);
                 expect(wallet.getEthereumAccounts()[0].address).toBe("0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4");
+
+                let key = wallet.getEthereumAccounts()[0].key;
+                expect(key.type).toBe("raw-pk");
+                expect(key["keyId"]).toBeDefined();
+                expect(key["seedId"]).toBeNull();
+                expect(key["hdPath"]).toBeNull();
+                expect(JSON.stringify(key)).not.toContain("fac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd");
             });
 
             test("Create and import 2 keys", () => {
@@ -175,6 +183,110 @@ describe("Wallets", () => {
 
     });
 
+    describe("Watch-only accounts", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("wallet-watch")
+            });
+        });
+
+        test("Add address", () => {
+            let id = vault.addWallet("watch");
+            let acc: AddAccount = {
+                blockchain: 100,
+                type: "watch-only",
+                key: {
+                    address: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3"
+                }
+            };
+            let accountId = vault.addAccount(id, acc);
+            expect(accountId).toBe(id + "-0");
+
+            let wallet = WalletsOp.of(vault.listWallets()).getWallet(id);
+            expect(wallet.getEthereumAccounts()[0].address).toBe("0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3");
+            expect(wallet.getEthereumAccounts()[0].watchOnly).toBeTruthy();
+            expect(wallet.getEthereumAccounts()[0].key.type).toBe("watch-only");
+            expect(wallet.getEthereumAccounts()[0].key["keyId"]).toBeNull();
+        });
+
+        test("Add xpub range", () => {
+            let id = vault.addWallet("watch");
+            let acc: AddAccount = {
+                blockchain: 100,
+                type: "watch-only",
+                key: {
+                    xpub: "xpub6BkyEFUnmCvXJdkApVfSA6vPBzrZnXH4foF54bGRM3gggQ4MYzaebpjmM2aTmcEg2mMR8RJmkMtGjThcxSQzvk3wtWzn766rm6VRVHSm2vb",
+                    start: 0,
+                    count: 2
+                }
+            };
+            vault.addAccount(id, acc);
+
+            let wallet = WalletsOp.of(vault.listWallets()).getWallet(id);
+            expect(wallet.value.accounts.length).toBe(2);
+            expect(wallet.getEthereumAccounts()[0].address).toBe("0x110c84fCC6A775f788d3CA6A9492Abd5B3fEc588".toLowerCase());
+            expect(wallet.getEthereumAccounts()[1].address).toBe("0xb4BbAaC4Acd7E86AF282e80C7a62fda78D071950".toLowerCase());
+        });
+
+        test("Reject invalid xpub range", () => {
+            let id = vault.addWallet("watch");
+            let xpub = "xpub6BkyEFUnmCvXJdkApVfSA6vPBzrZnXH4foF54bGRM3gggQ4MYzaebpjmM2aTmcEg2mMR8RJmkMtGjThcxSQzvk3wtWzn766rm6VRVHSm2vb";
+            expect(() => vault.addAccount(id, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {xpub, start: 0, count: 100000}
+            })).toThrow();
+            expect(() => vault.addAccount(id, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {xpub, start: 4294967295, count: 2}
+            })).toThrow();
+
+            let wallet = WalletsOp.of(vault.listWallets()).getWallet(id);
+            expect(wallet.value.accounts.length).toBe(0);
+        });
+
+        test("Cannot sign or export", () => {
+            let id = vault.addWallet("watch");
+            let accountId = vault.addAccount(id, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {
+                    address: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3"
+                }
+            });
+            let tx = {
+                from: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                value: "0x1051",
+                gas: "0x5208",
+                gasPrice: "0x77359400",
+                nonce: "0x2"
+            };
+            expect(() => vault.signTx(accountId, tx, "test")).toThrow("Watch-only");
+            expect(() => vault.exportRawPk(accountId, "test")).toThrow("Watch-only");
+        });
+
+        test("Stays watch-only without metadata", () => {
+            let dir = tempPath("wallet-watch");
+            let vault = new EmeraldVaultNative({dir});
+            let id = vault.addWallet("watch");
+            let accountId = vault.addAccount(id, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {
+                    address: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3"
+                }
+            });
+            fs.unlinkSync(dir + "/node-meta.json");
+
+            let wallet = WalletsOp.of(vault.listWallets()).getWallet(id);
+            expect(wallet.getEthereumAccounts()[0].watchOnly).toBeTruthy();
+            expect(() => vault.exportRawPk(accountId, "test")).toThrow("Watch-only");
+        });
+    });
+
     describe("Update wallet", () => {
         let vault: EmeraldVaultNative;
         beforeEach(() => {
@@ -203,5 +315,364 @@ describe("Wallets", () => {
             expect(wallet4.name).toBeNull();
         })
 
+        test("Doesn't overwrite invalid metadata", () => {
+            let dir = tempPath("wallet-meta");
+            vault = new EmeraldVaultNative({dir});
+            let walletId = vault.addWallet("test 1");
+            fs.writeFileSync(dir + "/node-meta.json", "{invalid");
+
+            expect(() => vault.getWallet(walletId)).toThrow();
+            expect(() => vault.updateWallet(walletId, {description: "test"})).toThrow();
+            expect(fs.readFileSync(dir + "/node-meta.json").toString()).toBe("{invalid");
+        });
+
+        test("Has timestamps", () => {
+            let start = Date.now();
+            let walletId = vault.addWallet("test 1");
+            let wallet1 = vault.getWallet(walletId);
+
+            expect(wallet1.createdAt).toBeGreaterThanOrEqual(start);
+            expect(wallet1.updatedAt).toBe(wallet1.createdAt);
+
+            vault.setWalletLabel(walletId, "test 2");
+            let wallet2 = vault.getWallet(walletId);
+            expect(wallet2.createdAt).toBe(wallet1.createdAt);
+            expect(wallet2.updatedAt).toBeGreaterThanOrEqual(wallet1.updatedAt);
+        })
+
+        test("Update details", () => {
+            let walletId = vault.addWallet("test 1");
+
+            let updated = vault.updateWallet(walletId, {
+                description: "Main wallet",
+                color: "#ff8800",
+                icon: "cat"
+            });
+            expect(updated).toBeTruthy();
+
+            let wallet1 = vault.getWallet(walletId);
+            expect(wallet1.name).toBe("test 1");
+            expect(wallet1.description).toBe("Main wallet");
+            expect(wallet1.color).toBe("#ff8800");
+            expect(wallet1.icon).toBe("cat");
+
+            vault.updateWallet(walletId, {name: "test 2", icon: null});
+            let wallet2 = vault.getWallet(walletId);
+            expect(wallet2.name).toBe("test 2");
+            expect(wallet2.description).toBe("Main wallet");
+            expect(wallet2.color).toBe("#ff8800");
+            expect(wallet2.icon).toBeNull();
+        })
+
+        test("Update unknown wallet", () => {
+            let updated = vault.updateWallet("3b6e4f20-23d8-4d2c-8b36-3c8e4b2f5b45", {description: "test"});
+            expect(updated).toBeFalsy();
+        })
+
+        test("List in explicit order", () => {
+            let walletId1 = vault.addWallet("test 1");
+            let walletId2 = vault.addWallet("test 2");
+            let walletId3 = vault.addWallet("test 3");
+
+            vault.updateWallet(walletId3, {order: 1});
+            vault.updateWallet(walletId2, {order: 2});
+
+            let wallets = vault.listWallets();
+            expect(wallets.map((w) => w.id)).toEqual([walletId3, walletId2, walletId1]);
+
+            vault.updateWallet(walletId3, {order: null});
+            wallets = vault.listWallets();
+            expect(wallets[0].id).toBe(walletId2);
+            expect(wallets[0].order).toBe(2);
+            expect(wallets[1].order).toBeNull();
+            expect(wallets[2].order).toBeNull();
+        })
+
+    });
+
+    describe("Account details", () => {
+        let dir: string;
+        let vault: EmeraldVaultNative;
+        let walletId: string;
+        beforeEach(() => {
+            dir = tempPath("wallet-account");
+            vault = new EmeraldVaultNative({
+                dir: dir
+            });
+            walletId = vault.addWallet("test");
+            ["0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3", "0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1"].forEach((address) => {
+                vault.addAccount(walletId, {
+                    blockchain: 100,
+                    type: "watch-only",
+                    key: {address}
+                });
+            });
+        });
+
+        test("Update label and description", () => {
+            let accountId = walletId + "-0";
+            let updated = vault.updateAccount(accountId, {label: "Savings", description: "Cold storage"});
+            expect(updated).toBeTruthy();
+
+            let account = vault.getWallet(walletId).accounts[0];
+            expect(account.label).toBe("Savings");
+            expect(account.description).toBe("Cold storage");
+            expect(account.hidden).toBeFalsy();
+
+            vault.updateAccount(accountId, {description: null});
+            account = vault.getWallet(walletId).accounts[0];
+            expect(account.label).toBe("Savings");
+            expect(account.description).toBeNull();
+
+            let other = vault.getWallet(walletId).accounts[1];
+            expect(other.label).toBeNull();
+        });
+
+        test("Update unknown account", () => {
+            let updated = vault.updateAccount(walletId + "-5", {label: "test"});
+            expect(updated).toBeFalsy();
+        });
+
+        test("Hidden account is not listed by default", () => {
+            vault.updateAccount(walletId + "-0", {hidden: true});
+
+            let accounts = vault.getWallet(walletId).accounts;
+            expect(accounts.length).toBe(1);
+            expect(accounts[0].id).toBe(walletId + "-1");
+
+            let vaultAll = new EmeraldVaultNative({
+                dir: dir,
+                showHidden: true
+            });
+            accounts = vaultAll.getWallet(walletId).accounts;
+            expect(accounts.length).toBe(2);
+            expect(accounts[0].hidden).toBeTruthy();
+            expect(accounts[1].hidden).toBeFalsy();
+
+            vaultAll.updateAccount(walletId + "-0", {hidden: false});
+            accounts = vault.getWallet(walletId).accounts;
+            expect(accounts.length).toBe(2);
+        });
+
+        test("Details removed with account", () => {
+            vault.updateAccount(walletId + "-1", {label: "Savings"});
+            vault.removeAccount(walletId + "-1");
+            let accountId = vault.addAccount(walletId, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {address: "0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1"}
+            });
+            // id of a removed account is not used again
+            expect(accountId).toBe(walletId + "-2");
+            let accounts = vault.getWallet(walletId).accounts;
+            expect(accounts.map((a) => a.id)).toEqual([walletId + "-0", walletId + "-2"]);
+            expect(accounts[1].label).toBeNull();
+        });
+    });
+
+    describe("Move account", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("wallet-move")
+            });
+        });
+
+        test("Move with key", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            let walletId2 = vault.addWallet("wallet 2");
+            vault.addAccount(walletId2, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {address: "0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1"}
+            });
+            let accountId = vault.addAccount(walletId1, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test"
+            });
+            vault.updateAccount(accountId, {label: "Main"});
+
+            let movedId = vault.moveAccount(accountId, walletId2);
+            expect(movedId).toBe(walletId2 + "-1");
+
+            expect(vault.getWallet(walletId1).accounts.length).toBe(0);
+            let accounts = vault.getWallet(walletId2).accounts;
+            expect(accounts.length).toBe(2);
+            expect(accounts[1].id).toBe(movedId);
+            expect(accounts[1].label).toBe("Main");
+
+            let key = vault.exportRawPk(movedId, "test");
+            expect(key).toBe("0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd");
+        });
+
+        test("Fails for same address in target", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            let walletId2 = vault.addWallet("wallet 2");
+            let accountId = vault.addAccount(walletId1, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {address: "0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1"}
+            });
+            vault.addAccount(walletId2, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {address: "0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1"}
+            });
+
+            expect(() => vault.moveAccount(accountId, walletId2)).toThrow();
+            expect(vault.getWallet(walletId1).accounts.length).toBe(1);
+            expect(vault.getWallet(walletId2).accounts.length).toBe(1);
+        });
+
+        test("Fails for unknown account", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            let walletId2 = vault.addWallet("wallet 2");
+            expect(() => vault.moveAccount(walletId1 + "-0", walletId2)).toThrow();
+        });
+    });
+
+    describe("Merge and split", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(() => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("wallet-merge")
+            });
+        });
+
+        function addWatch(walletId: string, address: string): string {
+            return vault.addAccount(walletId, {
+                blockchain: 100,
+                type: "watch-only",
+                key: {address}
+            });
+        }
+
+        test("Merge wallets", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            let walletId2 = vault.addWallet("wallet 2");
+            let walletId3 = vault.addWallet("wallet 3");
+            addWatch(walletId1, "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3");
+            let accountId = vault.addAccount(walletId2, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test"
+            });
+            vault.updateAccount(accountId, {label: "Main"});
+            addWatch(walletId3, "0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1");
+
+            let merged = vault.mergeWallets([walletId1, walletId2], "merged");
+
+            let wallets = vault.listWallets();
+            expect(wallets.length).toBe(2);
+            expect(wallets.map((w) => w.id)).not.toContain(walletId1);
+            expect(wallets.map((w) => w.id)).not.toContain(walletId2);
+            expect(vault.getWallet(walletId3).accounts.length).toBe(1);
+
+            let wallet = vault.getWallet(merged);
+            expect(wallet.name).toBe("merged");
+            expect(wallet.accounts.map((a) => a.id)).toEqual([merged + "-0", merged + "-1"]);
+            expect(wallet.accounts[1].label).toBe("Main");
+
+            let key = vault.exportRawPk(merged + "-1", "test");
+            expect(key).toBe("0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd");
+        });
+
+        test("Merge keeps one copy of an address", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            let walletId2 = vault.addWallet("wallet 2");
+            addWatch(walletId1, "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4");
+            vault.addAccount(walletId2, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test"
+            });
+
+            let merged = vault.mergeWallets([walletId1, walletId2]);
+
+            let wallet = vault.getWallet(merged);
+            expect(wallet.accounts.length).toBe(1);
+            expect(wallet.accounts[0]["watchOnly"]).toBeFalsy();
+        });
+
+        test("Merge keeps one copy of a private key", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            let walletId2 = vault.addWallet("wallet 2");
+            [walletId1, walletId2].forEach((walletId) => {
+                vault.addAccount(walletId, {
+                    blockchain: 100,
+                    type: "raw-pk-hex",
+                    key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                    password: "test"
+                });
+            });
+
+            let merged = vault.mergeWallets([walletId1, walletId2]);
+
+            let wallet = vault.getWallet(merged);
+            expect(wallet.accounts.length).toBe(1);
+            let key = vault.exportRawPk(merged + "-0", "test");
+            expect(key).toBe("0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd");
+        });
+
+        test("Merge keeps wallet details", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            let walletId2 = vault.addWallet("wallet 2");
+            vault.updateWallet(walletId1, {color: "#ff8800"});
+            vault.updateWallet(walletId2, {description: "Second", color: "#00ff00", order: 2});
+
+            let merged = vault.mergeWallets([walletId1, walletId2]);
+
+            let wallet = vault.getWallet(merged);
+            expect(wallet.name).toBe("wallet 1");
+            expect(wallet.description).toBe("Second");
+            expect(wallet.color).toBe("#ff8800");
+            expect(wallet.order).toBe(2);
+        });
+
+        test("Merge fails for unknown wallet", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            expect(() => vault.mergeWallets([walletId1, "3b6e4f20-23d8-4d2c-8b36-3c8e4b2f5b45"])).toThrow();
+            expect(vault.listWallets().length).toBe(1);
+        });
+
+        test("Split wallet", () => {
+            let walletId = vault.addWallet("wallet 1");
+            addWatch(walletId, "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3");
+            let accountId1 = addWatch(walletId, "0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1");
+            let accountId2 = addWatch(walletId, "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4");
+            vault.updateAccount(accountId2, {label: "Third"});
+
+            let split = vault.splitWallet([accountId1, accountId2], "split");
+
+            let source = vault.getWallet(walletId);
+            expect(source.accounts.map((a) => a.id)).toEqual([walletId + "-0"]);
+
+            let wallet = vault.getWallet(split);
+            expect(wallet.name).toBe("split");
+            expect(wallet.accounts.map((a) => a.id)).toEqual([split + "-0", split + "-1"]);
+            expect(wallet.accounts[0]["address"]).toBe("0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1");
+            expect(wallet.accounts[1].label).toBe("Third");
+        });
+
+        test("Split fails for unknown account", () => {
+            let walletId = vault.addWallet("wallet 1");
+            addWatch(walletId, "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3");
+            expect(() => vault.splitWallet([walletId + "-0", walletId + "-3"])).toThrow();
+            expect(vault.listWallets().length).toBe(1);
+            expect(vault.getWallet(walletId).accounts.length).toBe(1);
+        });
+
+        test("Split fails for accounts of different wallets", () => {
+            let walletId1 = vault.addWallet("wallet 1");
+            let walletId2 = vault.addWallet("wallet 2");
+            let accountId1 = addWatch(walletId1, "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3");
+            let accountId2 = addWatch(walletId2, "0x5b30de96fdf94ac6c5b4a8c243f991c649d66fa1");
+            expect(() => vault.splitWallet([accountId1, accountId2])).toThrow();
+            expect(vault.listWallets().length).toBe(2);
+        });
     });
 });
\ No newline at end of file
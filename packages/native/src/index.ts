@@ -1,3 +1,4 @@
 export {EmeraldVaultNative} from './EmeraldVaultNative';
 
 
+export {StatusCode, StatusError} from './types';
@@ -1,10 +1,31 @@
 export type Config = {
-    dir?: string | null
+    dir?: string | null,
+    // Hardware Wallet access, "hid" by default, which finds connected Ledger and Trezor devices. Must be used only for
+    // testing, the "emulator" is a Ledger with a test seed and the "trezor-emulator" is a Trezor with a test seed.
+    // State of an emulator can be set as "emulator:locked", "emulator:no-app", "emulator:reject", "trezor-emulator:locked"
+    // or "trezor-emulator:reject". Several emulated devices are set as a comma-separated list, like "emulator,trezor-emulator"
+    hardware?: string | null,
+    // include hidden accounts into the list of wallets, false by default
+    showHidden?: boolean | null
 }
 
 export enum StatusCode {
     UNKNOWN = 0,
-    NOT_IMPLEMENTED = 1
+    NOT_IMPLEMENTED = 1,
+    VAULT_ERROR = 2,
+    WATCH_ONLY = 3,
+    HW_ERROR = 4,
+    HW_NOT_CONNECTED = 5,
+    HW_LOCKED = 6,
+    HW_WRONG_APP = 7,
+    HW_REJECTED = 8
+}
+
+/**
+ * Error thrown when the code of the failure matters to the caller, e.g. to tell the user to unlock a Ledger
+ */
+export type StatusError = Error & {
+    code: StatusCode
 }
 
 export type Status<T> = {